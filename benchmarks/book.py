@@ -87,6 +87,144 @@ def benchmark_order_book_matching(
     print(f"Orders per second: {orders_per_second:.2f}\n")
 
 
+def benchmark_order_book_snapshot(resting_orders: int = 100_000, rounds: int = 10):
+    """Benchmark JSON and binary snapshots of a populated OrderBook.
+
+    Args:
+        resting_orders (int): Number of orders to generate; about half of them rest
+            on the book, with bids and asks kept apart so nothing matches.
+        rounds (int): Number of snapshot/restore round trips per format.
+    """
+    print(
+        f"Snapshotting an OrderBook seeded from {resting_orders} orders "
+        f"{rounds} times per format....\n"
+    )
+
+    order_book = lb.OrderBook(tick_size=0.01)
+    for order in generate_orders(order_book, resting_orders // 2, (1.0, 10.0)):
+        if order.side == lb.OrderType.Buy:
+            order_book.add(order)
+    for order in generate_orders(order_book, resting_orders // 2, (11.0, 20.0)):
+        if order.side == lb.OrderType.Sell:
+            order_book.add(order)
+
+    for name, dump, load in (
+        ("JSON", lb.OrderBook.to_json, lb.OrderBook.from_json),
+        ("bytes", lb.OrderBook.to_bytes, lb.OrderBook.from_bytes),
+    ):
+        start_time = time.time()
+        for _ in range(rounds):
+            load(dump(order_book))
+        total_time = time.time() - start_time
+        print(f"{name}: {total_time / rounds * 1000:.2f} ms per round trip.")
+    print()
+
+
+def benchmark_order_book_deep_partial_fills(
+    levels: int = 1_000, orders_per_level: int = 100, takers: int = 500_000
+):
+    """Benchmark small takers partially filling the front of a deep book.
+
+    Every taker trades against the order at the front of the best ask level
+    without exhausting it, which exercises the in-place update of partially
+    filled resting orders.
+
+    Args:
+        levels (int): Number of ask price levels to seed.
+        orders_per_level (int): Number of resting orders per price level.
+        takers (int): Number of IOC buy orders to process.
+    """
+    print(
+        f"Seeding OrderBook with {levels} levels of {orders_per_level} orders "
+        f"and processing {takers} partially filling takers....\n"
+    )
+
+    order_book = lb.OrderBook(tick_size=0.01)
+    for level in range(levels):
+        price = 100.0 + level * 0.01
+        for _ in range(orders_per_level):
+            order_book.add(order_book.create_order(lb.OrderType.Sell, price, 10.0))
+
+    taker_orders = [
+        order_book.create_order(
+            lb.OrderType.Buy, 200.0, 1.0, time_in_force=lb.TimeInForce.IOC
+        )
+        for _ in range(takers)
+    ]
+
+    start_time = time.time()
+    for order in taker_orders:
+        order_book.add(order)
+    total_time = time.time() - start_time
+
+    print(f"Processed {takers} takers in {total_time:.2f} seconds.")
+    print(f"Orders per second: {takers / total_time:.2f}\n")
+
+
+def benchmark_order_book_resting_adds(orders: int = 1_000_000):
+    """Benchmark adding orders that rest without matching.
+
+    Bids and asks are kept on opposite sides of a wide spread, so every `.add()`
+    call takes the resting path, where the order is copied into the order map
+    once and moved into its price level.
+
+    Args:
+        orders (int): Number of orders to add.
+    """
+    print(f"Adding {orders} non-crossing orders to an empty OrderBook....\n")
+
+    order_book = lb.OrderBook(tick_size=0.01)
+    resting_orders = [
+        order_book.create_order(lb.OrderType.Buy, random.uniform(1.0, 10.0), 1.0)
+        if i % 2 == 0
+        else order_book.create_order(lb.OrderType.Sell, random.uniform(11.0, 20.0), 1.0)
+        for i in range(orders)
+    ]
+
+    start_time = time.time()
+    for order in resting_orders:
+        order_book.add(order)
+    total_time = time.time() - start_time
+
+    print(f"Rested {orders} orders in {total_time:.2f} seconds.")
+    print(f"Orders per second: {orders / total_time:.2f}\n")
+
+
+def benchmark_order_book_expire(resting_orders: int = 100_000, calls: int = 1_000):
+    """Benchmark expire() calls that find nothing to expire on a large book.
+
+    Every order carries a later deadline, so each call should only look at the
+    earliest deadline instead of scanning the whole book.
+
+    Args:
+        resting_orders (int): Number of expiring orders to rest on the book.
+        calls (int): Number of `.expire()` calls to time.
+    """
+    print(
+        f"Calling expire() {calls} times on an OrderBook with {resting_orders} "
+        f"expiring orders....\n"
+    )
+
+    order_book = lb.OrderBook(tick_size=0.01)
+    for i in range(resting_orders):
+        price = 1.0 + (i % 500) * 0.01
+        order_book.add(
+            order_book.create_order(lb.OrderType.Buy, price, 1.0, expires_at=1_000 + i)
+        )
+
+    start_time = time.time()
+    for _ in range(calls):
+        order_book.expire(999)
+    total_time = time.time() - start_time
+
+    print(f"Ran {calls} expire() calls in {total_time:.2f} seconds.")
+    print(f"Calls per second: {calls / total_time:.2f}\n")
+
+
 if __name__ == "__main__":
     print("Benchmarking litebook.OrderBook performance:\n")
     benchmark_order_book_matching()
+    benchmark_order_book_snapshot()
+    benchmark_order_book_deep_partial_fills()
+    benchmark_order_book_resting_adds()
+    benchmark_order_book_expire()
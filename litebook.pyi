@@ -1,4 +1,103 @@
-from typing import List, Optional
+from typing import Callable, Dict, List, Optional, Tuple
+
+class OrderRejectReason:
+    """Represents why an order or order change was rejected."""
+
+    NonPositivePrice: "OrderRejectReason"
+    """The price (or stop price) is not positive."""
+
+    NonPositiveQuantity: "OrderRejectReason"
+    """The quantity is not positive."""
+
+    PriceOffTick: "OrderRejectReason"
+    """The price is not a multiple of the tick size and `strict` was set, or not a
+    multiple of the book's tick multiple."""
+
+    BelowMinSize: "OrderRejectReason"
+    """The quantity is below the book's minimum order size."""
+
+    AboveMaxSize: "OrderRejectReason"
+    """The quantity is above the book's maximum order size."""
+
+    OffLotSize: "OrderRejectReason"
+    """The quantity is not a multiple of the book's lot size."""
+
+    FractionalQuantity: "OrderRejectReason"
+    """The quantity is not a whole number in a book with integer quantities."""
+
+    InvalidDisplayQuantity: "OrderRejectReason"
+    """An iceberg's display quantity is not positive or exceeds its quantity."""
+
+    DuplicateOrderId: "OrderRejectReason"
+    """An order with the same ID is still tracked by the book: resting, a pending
+    stop, or filled. IDs of canceled and expired orders may be reused."""
+
+    NotAllowedInAuction: "OrderRejectReason"
+    """Only GTC limit orders are accepted during an auction."""
+
+    PostOnlyWouldMatch: "OrderRejectReason"
+    """A post-only order would immediately match."""
+
+    PegUnavailable: "OrderRejectReason"
+    """A pegged order's reference price does not exist."""
+
+    OutsidePriceBand: "OrderRejectReason"
+    """The price is too far from the last trade."""
+
+    QuantityIncrease: "OrderRejectReason"
+    """A quantity increase was refused because `reject_increase` was set."""
+
+    NotResting: "OrderRejectReason"
+    """The order to change is not resting on the book."""
+
+    ReduceOnlyWouldIncrease: "OrderRejectReason"
+    """A reduce-only order has no position left to reduce."""
+
+    InvalidMinFillQuantity: "OrderRejectReason"
+    """A minimum fill quantity is not positive, exceeds the order's quantity, or is
+    set on a FOK order."""
+
+    ReductionTooLarge: "OrderRejectReason"
+    """A reduction exceeds the order's remaining quantity."""
+
+    EmptyOrderId: "OrderRejectReason"
+    """An explicit order ID is empty."""
+
+    NegativeSlippage: "OrderRejectReason"
+    """A market order's slippage collar is negative."""
+
+    CrossedBook: "OrderRejectReason"
+    """The add would leave the book locked or crossed."""
+
+    FilteredOut: "OrderRejectReason"
+    """The book's order filter refused the order."""
+
+    NotRestable: "OrderRejectReason"
+    """The order is not an open GTC limit order, so it cannot be seeded."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this OrderRejectReason is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same OrderRejectReason, False otherwise.
+        """
+        ...
+
+class OrderRejectedError(ValueError):
+    """Raised when an order or order change is rejected.
+
+    The exception message describes the rejection, while `reason` identifies it
+    for programmatic handling.
+    """
+
+    reason: OrderRejectReason
+    """The reason the order was rejected."""
+
+class PriceBandError(OrderRejectedError):
+    """Raised when an order is rejected for pricing outside the book's price band."""
 
 class OrderType:
     """Represents the side of an order, either Buy or Sell."""
@@ -43,11 +142,358 @@ class OrderStatus:
         """
         ...
 
+class TimeInForce:
+    """Represents how long an order remains active (GTC, IOC, or FOK)."""
+
+    GTC: "TimeInForce"
+    """A TimeInForce indicating the order rests until filled or canceled."""
+
+    IOC: "TimeInForce"
+    """A TimeInForce indicating the unfilled remainder is canceled immediately."""
+
+    FOK: "TimeInForce"
+    """A TimeInForce indicating the order must fill completely or not at all."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this TimeInForce is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same TimeInForce, False otherwise.
+        """
+        ...
+
+class OrderKind:
+    """Represents how an order is priced, either Limit or Market."""
+
+    Limit: "OrderKind"
+    """An OrderKind for orders that trade at their price or better."""
+
+    Market: "OrderKind"
+    """An OrderKind for orders that trade at any price and never rest."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this OrderKind is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same OrderKind, False otherwise.
+        """
+        ...
+
+class PegReference:
+    """Represents the price a pegged order tracks.
+
+    Pegged orders are ignored when finding the reference, so a peg never follows
+    itself or another peg.
+    """
+
+    BestBid: "PegReference"
+    """Track the best bid."""
+
+    BestAsk: "PegReference"
+    """Track the best ask."""
+
+    Mid: "PegReference"
+    """Track the midpoint of the best bid and ask, rounded down for buys and up for
+    sells when it falls between ticks."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this PegReference is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same PegReference, False otherwise.
+        """
+        ...
+
+class SelfTradePrevention:
+    """Represents how a match between two orders with the same owner is prevented."""
+
+    CancelNewest: "SelfTradePrevention"
+    """Cancel the incoming order and leave the resting order."""
+
+    CancelOldest: "SelfTradePrevention"
+    """Cancel the resting order and keep matching the incoming order."""
+
+    CancelBoth: "SelfTradePrevention"
+    """Cancel both orders."""
+
+    DecrementBoth: "SelfTradePrevention"
+    """Reduce both orders by the smaller quantity without a fill, canceling any
+    order that reaches zero."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this SelfTradePrevention is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same SelfTradePrevention, False otherwise.
+        """
+        ...
+
+class MatchingMode:
+    """Represents how an incoming order is allocated across a price level."""
+
+    Fifo: "MatchingMode"
+    """Fill resting orders in time priority, each in full before the next."""
+
+    ProRata: "MatchingMode"
+    """Split the traded quantity across every resting order at the level in
+    proportion to its visible quantity. Allocations are not rounded (except down to
+    whole units when `quantity_is_integer` is set); the last order in the queue
+    takes whatever remains of the traded quantity."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this MatchingMode is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same MatchingMode, False otherwise.
+        """
+        ...
+
+class FillPriceRule:
+    """Represents which price a match between two orders trades at.
+
+    Market orders carry no price, so they always trade at the resting price.
+    """
+
+    Seller: "FillPriceRule"
+    """Trade at the sell order's price, whichever side rests. This is the default."""
+
+    Maker: "FillPriceRule"
+    """Trade at the resting order's price."""
+
+    Taker: "FillPriceRule"
+    """Trade at the incoming order's price."""
+
+    Midpoint: "FillPriceRule"
+    """Trade halfway between the two prices, rounded to the nearest tick with halves
+    rounded away from zero."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this FillPriceRule is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same FillPriceRule, False otherwise.
+        """
+        ...
+
+class CrossedBookPolicy:
+    """Represents how an add that would leave the book locked or crossed is handled.
+
+    This can only happen when an order's matching price keeps it from trading with an
+    order it overlaps on display price.
+    """
+
+    Resolve: "CrossedBookPolicy"
+    """Re-run matching for the order that crossed the touch and cancel whatever still
+    cannot trade."""
+
+    Reject: "CrossedBookPolicy"
+    """Reject the add with `OrderRejectReason.CrossedBook`, leaving the book
+    unchanged."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this CrossedBookPolicy is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same CrossedBookPolicy, False otherwise.
+        """
+        ...
+
+class CancelOutcome:
+    """Represents the outcome of `OrderBook.cancel_detailed`."""
+
+    Canceled: "CancelOutcome"
+    """The order was removed from the book."""
+
+    NotFound: "CancelOutcome"
+    """No open order with the ID is tracked."""
+
+    FoundButNotInQueue: "CancelOutcome"
+    """The order is tracked as open but is neither queued at its price level nor a
+    pending stop. The book is inconsistent, so it is left unchanged."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this CancelOutcome is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same CancelOutcome, False otherwise.
+        """
+        ...
+
+class LevelPriority:
+    """Represents which resting order at the best price is matched first when the
+    book uses `MatchingMode.Fifo`. Pro-rata matching ignores it.
+    """
+
+    Time: "LevelPriority"
+    """Match the oldest order first. A partially filled order keeps its place at the
+    front, so the matching sequence is fully stable."""
+
+    Size: "LevelPriority"
+    """Match the order with the largest visible quantity first, breaking ties by
+    time. Partially filled orders keep their queue position, but may lose their turn
+    once their remaining size is no longer the largest."""
+
+    Random: "LevelPriority"
+    """Match a pseudo-randomly chosen order first. The choice is derived from the
+    incoming order's ID, so replaying the same events reproduces the same fills."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this LevelPriority is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same LevelPriority, False otherwise.
+        """
+        ...
+
+class RoundingMode:
+    """Represents how a price that is not a multiple of the tick size is rounded onto
+    the tick grid. Prices within floating-point noise of a tick always snap to it.
+    """
+
+    Nearest: "RoundingMode"
+    """Round to the nearest tick, with halves rounded away from zero."""
+
+    Up: "RoundingMode"
+    """Round to the tick above."""
+
+    Down: "RoundingMode"
+    """Round to the tick below."""
+
+    TowardConservative: "RoundingMode"
+    """Round buys down and sells up, so rounding never makes an order more
+    aggressive."""
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if this RoundingMode is equal to another object.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if both are the same RoundingMode, False otherwise.
+        """
+        ...
+
+class Event:
+    """Represents a mutating operation applied to an OrderBook, recorded so the book
+    can be rebuilt with `OrderBook.replay`."""
+
+    class Add:
+        """An order submitted with `add` or `add_at`."""
+
+        def __init__(self, order: "Order", timestamp: int) -> None: ...
+        @property
+        def order(self) -> "Order":
+            """Order: The order as it was submitted."""
+            ...
+
+        @property
+        def timestamp(self) -> int:
+            """int: The timestamp (in nanoseconds) stamped on the add's fills."""
+            ...
+
+    class Cancel:
+        """An order removed from the book by a cancel or expiry."""
+
+        def __init__(self, order_id: str) -> None: ...
+        @property
+        def order_id(self) -> str:
+            """str: The ID of the canceled order."""
+            ...
+
+    class Modify:
+        """A quantity change to a resting order."""
+
+        def __init__(self, order_id: str, new_quantity: float) -> None: ...
+        @property
+        def order_id(self) -> str:
+            """str: The ID of the modified order."""
+            ...
+
+        @property
+        def new_quantity(self) -> float:
+            """float: The order's new quantity."""
+            ...
+
+    class Reduce:
+        """A partial cancel of a resting order with `reduce`."""
+
+        def __init__(self, order_id: str, amount: float) -> None: ...
+        @property
+        def order_id(self) -> str:
+            """str: The ID of the reduced order."""
+            ...
+
+        @property
+        def amount(self) -> float:
+            """float: The quantity pulled from the order."""
+            ...
+
+    class RepricePegs:
+        """A call to `reprice_pegs`."""
+
+        def __init__(self, timestamp: int) -> None: ...
+        @property
+        def timestamp(self) -> int:
+            """int: The timestamp (in nanoseconds) stamped on the repricing's fills."""
+            ...
+
+    class StartAuction:
+        """A call to `start_auction`."""
+
+        def __init__(self) -> None: ...
+
+    class Uncross:
+        """A call to `uncross`."""
+
+        def __init__(self, timestamp: int) -> None: ...
+        @property
+        def timestamp(self) -> int:
+            """int: The timestamp (in nanoseconds) stamped on the auction's fills."""
+            ...
+
 class Fill:
     """Represents a trade fill with details about the matched quantity, price, and timing."""
 
     def __init__(
-        self, quantity: float, price: float, buy_id: str, sell_id: str, timestamp: int
+        self,
+        quantity: float,
+        price: float,
+        buy_id: str,
+        sell_id: str,
+        timestamp: int,
+        aggressor_side: Optional[OrderType] = None,
+        maker_fee: float = 0.0,
+        taker_fee: float = 0.0,
+        price_improvement: float = 0.0,
+        sequence: int = 0,
     ) -> None:
         """Initializes a Fill.
 
@@ -57,6 +503,16 @@ class Fill:
             buy_id (str): The identifier of the buy order.
             sell_id (str): The identifier of the sell order.
             timestamp (int): The timestamp (e.g., UNIX time) of the fill.
+            aggressor_side (Optional[OrderType], optional): The side of the incoming
+                (taker) order. Defaults to None (unknown).
+            maker_fee (float, optional): The fee charged to the resting order.
+                Defaults to 0.0.
+            taker_fee (float, optional): The fee charged to the incoming order.
+                Defaults to 0.0.
+            price_improvement (float, optional): How much better than its limit
+                price the incoming order traded. Defaults to 0.0.
+            sequence (int, optional): The fill's position in its book's execution
+                order. Defaults to 0 (unassigned).
         """
         ...
 
@@ -89,11 +545,56 @@ class Fill:
         """int: The timestamp of when the fill occurred."""
         ...
 
+    @property
+    def aggressor_side(self) -> Optional[OrderType]:
+        """Optional[OrderType]: The side of the incoming (taker) order that caused the
+        fill, or None if the fill was created without one."""
+        ...
+
+    @property
+    def maker_fee(self) -> float:
+        """float: The fee charged to the resting (maker) order, negative for a rebate."""
+        ...
+
+    @property
+    def taker_fee(self) -> float:
+        """float: The fee charged to the incoming (taker) order."""
+        ...
+
+    @property
+    def price_improvement(self) -> float:
+        """float: How much better than its limit price the incoming (taker) order
+        traded: the limit minus the fill price for a buy, the fill price minus the
+        limit for a sell. Never negative, and 0 for market orders."""
+        ...
+
+    @property
+    def sequence(self) -> int:
+        """int: The fill's position among every fill its book has produced,
+        starting at 1. Unlike timestamps, sequence numbers never tie, so they give
+        a total order of executions. Fills not produced by a book have sequence 0."""
+        ...
+
 class Order:
-    """Represents an order in the order book, including side, price, quantity, and status."""
+    """Represents an order in the order book, including side, price, quantity, and status.
+
+    Orders compare equal and hash by ID alone, not by their fields, so a filled or
+    modified copy of an order still equals the original. Orders can therefore be used
+    as dict keys and set members.
+    """
 
     def __init__(
-        self, side: OrderType, price_in_ticks: int, quantity: float
+        self,
+        side: OrderType,
+        price_in_ticks: int,
+        quantity: float,
+        time_in_force: TimeInForce = TimeInForce.GTC,
+        post_only: bool = False,
+        expires_at: Optional[int] = None,
+        timestamp: Optional[int] = None,
+        owner: Optional[str] = None,
+        matching_price_in_ticks: Optional[int] = None,
+        stp_group: Optional[str] = None,
     ) -> "Order":
         """Initializes an Order.
 
@@ -101,12 +602,81 @@ class Order:
             side (OrderType): The side of the order (Buy or Sell).
             price_in_ticks (int): The price, represented in ticks.
             quantity (float): The amount of the instrument to trade.
+            time_in_force (TimeInForce, optional): How long the order remains active.
+                Defaults to TimeInForce.GTC.
+            post_only (bool, optional): If True, the order is rejected instead of
+                taking liquidity. Defaults to False.
+            expires_at (Optional[int], optional): The expiry time in nanoseconds since
+                the Unix epoch. Defaults to None (no expiry).
+            timestamp (Optional[int], optional): The creation time in nanoseconds since
+                the Unix epoch. Defaults to None (the current wall-clock time).
+            owner (Optional[str], optional): The account used for self-trade
+                prevention. Defaults to None.
+            matching_price_in_ticks (Optional[int], optional): The price in ticks
+                used for matching instead of `price_in_ticks`. Defaults to None
+                (match at `price_in_ticks`).
+            stp_group (Optional[str], optional): The self-trade prevention group,
+                checked instead of `owner` when set. Defaults to None.
 
         Returns:
             Order: An instance of the Order class.
+
+        Raises:
+            OrderRejectedError: If `price_in_ticks`, `matching_price_in_ticks` or
+                `quantity` is not positive. Use `OrderBook.create_order` for books
+                that allow negative prices.
+        """
+        ...
+
+    @staticmethod
+    def with_id(
+        id: str,
+        side: OrderType,
+        price_in_ticks: int,
+        quantity: float,
+        time_in_force: TimeInForce = TimeInForce.GTC,
+        post_only: bool = False,
+        expires_at: Optional[int] = None,
+        timestamp: Optional[int] = None,
+        owner: Optional[str] = None,
+        matching_price_in_ticks: Optional[int] = None,
+        stp_group: Optional[str] = None,
+    ) -> "Order":
+        """Creates an Order with an explicit ID instead of a generated UUID.
+
+        Use it to rebuild orders whose IDs were assigned by an external system or
+        recorded in a log. The other arguments are the same as for `Order(...)`.
+
+        Args:
+            id (str): The order ID to use.
+            side (OrderType): The side of the order (Buy or Sell).
+            price_in_ticks (int): The price, represented in ticks.
+            quantity (float): The amount of the instrument to trade.
+
+        Returns:
+            Order: An instance of the Order class with the given ID.
+
+        Raises:
+            OrderRejectedError: If `id` is empty, or for the same reasons as
+                `Order(...)`.
+        """
+        ...
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if another object is the same order, by ID.
+
+        Args:
+            other (object): The object to compare with.
+
+        Returns:
+            bool: True if `other` is an Order with the same ID, False otherwise.
         """
         ...
 
+    def __hash__(self) -> int:
+        """Returns a hash of the order ID, consistent with `__eq__`."""
+        ...
+
     def __repr__(self) -> str:
         """Returns a string representation of the Order."""
         ...
@@ -114,6 +684,8 @@ class Order:
     def can_match(self, other: "Order") -> bool:
         """Determines if this order can be matched with another order.
 
+        Prices are compared using each order's `matching_price_in_ticks`.
+
         Args:
             other (Order): Another order to compare against.
 
@@ -123,15 +695,26 @@ class Order:
         """
         ...
 
-    def fill(self, incoming: "Order", tick_size: float) -> Optional[Fill]:
+    def fill(
+        self, incoming: "Order", tick_size: float, timestamp: Optional[int] = None
+    ) -> Optional[Fill]:
         """Attempts to fill this order with an incoming order.
 
+        The orders match on their matching prices, but the fill trades at the sell
+        order's display price.
+
         Args:
             incoming (Order): The incoming order attempting to match.
             tick_size (float): The minimum price increment.
+            timestamp (Optional[int], optional): The fill time in nanoseconds since
+                the Unix epoch. Defaults to None (the current wall-clock time).
 
         Returns:
             Optional[Fill]: A Fill object if a match occurs, otherwise None.
+
+        Raises:
+            ValueError: If `tick_size` is not a positive, finite number with an exact
+                decimal form of at most 15 places.
         """
         ...
 
@@ -160,7 +743,7 @@ class Order:
 
     @property
     def quantity(self) -> float:
-        """float: The quantity of the order remaining."""
+        """float: The visible quantity of the order remaining."""
         ...
 
     @property
@@ -173,76 +756,1307 @@ class Order:
         """int: The time when this order was created (e.g., as a UNIX timestamp)."""
         ...
 
-class OrderBook:
-    """Represents an order book, which manages active orders and executes trades."""
+    @property
+    def time_in_force(self) -> TimeInForce:
+        """TimeInForce: How long this order remains active."""
+        ...
 
-    def __init__(self, *, tick_size: float = 0.01) -> None:
-        """Initializes an OrderBook.
+    @property
+    def display_quantity(self) -> Optional[float]:
+        """Optional[float]: The visible slice size for iceberg orders, otherwise None."""
+        ...
 
-        Args:
-            tick_size (float, optional): The minimum price increment for orders. Defaults to 0.01.
-        """
+    @property
+    def hidden_quantity(self) -> float:
+        """float: The iceberg reserve not yet displayed on the book."""
         ...
 
-    def create_order(self, side: OrderType, price: float, quantity: float) -> Order:
-        """Creates a new order in the order book.
+    @property
+    def kind(self) -> OrderKind:
+        """OrderKind: Whether this is a limit or market order."""
+        ...
 
-        Args:
-            side (OrderType): The side of the order (Buy or Sell).
-            price (float): The price of the order in floating point.
-            quantity (float): The quantity of the instrument to trade.
+    @property
+    def stop_price_in_ticks(self) -> Optional[int]:
+        """Optional[int]: The stop trigger price in ticks, or None if not a stop order."""
+        ...
 
-        Returns:
-            Order: The newly created Order object.
-        """
+    @property
+    def expires_at(self) -> Optional[int]:
+        """Optional[int]: The expiry time in nanoseconds, or None if the order never expires."""
+        ...
+
+    @property
+    def owner(self) -> Optional[str]:
+        """Optional[str]: The account used for self-trade prevention, if any."""
+        ...
+
+    @property
+    def stp_group(self) -> Optional[str]:
+        """Optional[str]: The self-trade prevention group, if any. When set, it takes
+        priority over `owner`: the order only self-trades with orders in the same
+        group, and never with ungrouped orders."""
+        ...
+
+    @property
+    def max_slippage_ticks(self) -> Optional[int]:
+        """Optional[int]: How many ticks past the opposite touch a collared market
+        order may trade, if any."""
+        ...
+
+    @property
+    def original_quantity(self) -> float:
+        """float: The quantity the order was created with, including any hidden
+        iceberg reserve. Unlike `quantity`, it is never changed by fills,
+        modifications or reductions."""
+        ...
+
+    @property
+    def filled_quantity(self) -> float:
+        """float: The quantity executed across all of the order's fills. A Filled
+        order has nothing remaining, so this is its whole size: `original_quantity`
+        as amended by any `modify_quantity` or `reduce` calls made while it
+        rested."""
+        ...
+
+    @property
+    def remaining_quantity(self) -> float:
+        """float: The quantity still left to fill, including any hidden iceberg
+        reserve."""
+        ...
+
+    @property
+    def fill_ratio(self) -> float:
+        """float: The filled quantity over the filled plus remaining quantity: 0.0
+        for an unfilled order, between 0.0 and 1.0 for a partially filled one and
+        1.0 for a Filled order."""
+        ...
+
+    @property
+    def post_only(self) -> bool:
+        """bool: Whether this order is rejected instead of taking liquidity."""
+        ...
+
+    @property
+    def peg_reference(self) -> Optional[PegReference]:
+        """Optional[PegReference]: The price this order tracks, or None if it is not
+        pegged."""
+        ...
+
+    @property
+    def peg_offset_ticks(self) -> int:
+        """int: The distance in ticks from the peg reference."""
+        ...
+
+    @property
+    def reduce_only(self) -> bool:
+        """bool: Whether the order may only reduce its owner's position."""
+        ...
+
+    @property
+    def min_fill_quantity(self) -> Optional[float]:
+        """Optional[float]: The smallest execution the order will take, if any."""
+        ...
+
+    @property
+    def matching_price_in_ticks(self) -> int:
+        """int: The price in ticks used for matching, which is `price_in_ticks`
+        unless the order was given a separate matching price."""
+        ...
+
+class OrderBook:
+    """Represents an order book, which manages active orders and executes trades."""
+
+    def __init__(
+        self,
+        *,
+        tick_size: float = 0.01,
+        stp_policy: SelfTradePrevention = SelfTradePrevention.CancelNewest,
+        matching_mode: MatchingMode = MatchingMode.Fifo,
+        fill_price_rule: FillPriceRule = FillPriceRule.Seller,
+        min_quantity: Optional[float] = None,
+        max_quantity: Optional[float] = None,
+        lot_size: Optional[float] = None,
+        price_band_ticks: Optional[int] = None,
+        level_priority: LevelPriority = LevelPriority.Time,
+        quantity_is_integer: bool = False,
+        maker_fee_bps: float = 0.0,
+        taker_fee_bps: float = 0.0,
+        max_orders: Optional[int] = None,
+        crossed_book_policy: CrossedBookPolicy = CrossedBookPolicy.Resolve,
+        tick_multiple: int = 1,
+        allow_negative_prices: bool = False,
+    ) -> None:
+        """Initializes an OrderBook.
+
+        Args:
+            tick_size (float, optional): The minimum price increment for orders. Defaults to 0.01.
+            stp_policy (SelfTradePrevention, optional): How matches between orders
+                with the same owner are prevented. Defaults to CancelNewest.
+            matching_mode (MatchingMode, optional): How an incoming order is
+                allocated across the resting orders at a price level. Defaults to Fifo.
+            fill_price_rule (FillPriceRule, optional): Which price a match trades at.
+                Defaults to Seller.
+            min_quantity (Optional[float], optional): The smallest accepted order
+                quantity. Defaults to None (unconstrained).
+            max_quantity (Optional[float], optional): The largest accepted order
+                quantity. Defaults to None (unconstrained).
+            lot_size (Optional[float], optional): Order quantities must be a multiple
+                of this size. Defaults to None (unconstrained).
+            price_band_ticks (Optional[int], optional): Limit orders priced more than
+                this many ticks from the last trade price are rejected with a
+                PriceBandError. Defaults to None (no band).
+            level_priority (LevelPriority, optional): Which resting order at a price
+                level is matched first in Fifo mode. Defaults to Time.
+            quantity_is_integer (bool, optional): Whether every order quantity must be
+                a whole number, for markets that trade whole shares or contracts.
+                Pro-rata shares are then rounded down to whole units. Defaults to False.
+            maker_fee_bps (float, optional): The fee charged to the resting side of
+                each fill, in basis points of its notional. A negative fee is a
+                rebate. Defaults to 0.0.
+            taker_fee_bps (float, optional): The fee charged to the incoming side of
+                each fill, in basis points of its notional. Defaults to 0.0.
+            max_orders (Optional[int], optional): The most orders that may rest on
+                the book. An add that exceeds it evicts the oldest order at the
+                least aggressive price (the lowest bid or highest ask), marking it
+                canceled. Defaults to None (unbounded).
+            crossed_book_policy (CrossedBookPolicy, optional): How an add that
+                would leave the book locked or crossed is handled. Defaults to
+                Resolve.
+            tick_multiple (int, optional): The number of ticks limit prices must be
+                a multiple of, enforcing a coarser grid than the tick. Defaults to 1
+                (every tick is allowed).
+            allow_negative_prices (bool, optional): Whether orders created by the
+                book may have zero or negative prices, as for calendar spreads. They
+                match on them as usual. Defaults to False.
+
+        Raises:
+            ValueError: If `tick_size` is not a positive, finite number with an exact
+                decimal form of at most 15 places, if a fee rate is not finite, or if
+                `max_orders` or `tick_multiple` is not positive.
+        """
+        ...
+
+    def create_order(
+        self,
+        side: OrderType,
+        price: float,
+        quantity: float,
+        time_in_force: TimeInForce = TimeInForce.GTC,
+        post_only: bool = False,
+        expires_at: Optional[int] = None,
+        timestamp: Optional[int] = None,
+        owner: Optional[str] = None,
+        strict: bool = False,
+        rounding: RoundingMode = RoundingMode.Nearest,
+        reduce_only: bool = False,
+        min_fill_quantity: Optional[float] = None,
+        matching_price: Optional[float] = None,
+        stp_group: Optional[str] = None,
+    ) -> Order:
+        """Creates a new order in the order book.
+
+        The price is rounded onto the tick grid according to `rounding` unless
+        `strict` is set.
+
+        An order with `min_fill_quantity` only takes executions of at least that
+        size (or its whole remainder, if smaller) when added. Matching stops at the
+        first resting order (in pro-rata books, the first price level) that cannot
+        meet the minimum, and the unfilled remainder is canceled whatever the time
+        in force, so an IOC behaves as usual and a GTC order never rests across the
+        book. An order that simply runs out of crossing liquidity rests as usual,
+        and the minimum no longer applies once it rests.
+
+        An order with a `matching_price` matches as if it were at that price, while
+        it still rests at, and reports fills at, its display `price`. The book walks
+        price levels by display price and stops at a resting order whose matching
+        price does not cross.
+
+        Args:
+            side (OrderType): The side of the order (Buy or Sell).
+            price (float): The price of the order in floating point.
+            quantity (float): The quantity of the instrument to trade.
+            time_in_force (TimeInForce, optional): How long the order remains active.
+                Defaults to TimeInForce.GTC.
+            post_only (bool, optional): If True, the order is rejected instead of
+                taking liquidity. Defaults to False.
+            expires_at (Optional[int], optional): The expiry time in nanoseconds since
+                the Unix epoch. Defaults to None (no expiry).
+            timestamp (Optional[int], optional): The creation time in nanoseconds since
+                the Unix epoch. Defaults to None (the current wall-clock time).
+            owner (Optional[str], optional): The account used for self-trade
+                prevention. Defaults to None.
+            strict (bool, optional): If True, reject a price that is not a multiple
+                of the tick size instead of rounding it. Defaults to False.
+            rounding (RoundingMode, optional): How an off-grid price is rounded
+                to a tick. Defaults to RoundingMode.Nearest.
+            reduce_only (bool, optional): If True, the order is trimmed when added so
+                that it can only reduce its owner's position. Defaults to False.
+            min_fill_quantity (Optional[float], optional): The smallest execution
+                the order will take when added. Not allowed for FOK orders, which
+                already fill in full. Defaults to None.
+            matching_price (Optional[float], optional): The price used for matching,
+                rounded like `price`. Defaults to None (match at `price`).
+            stp_group (Optional[str], optional): The self-trade prevention group,
+                shared by every account that must not trade with another. Checked
+                instead of `owner` when set. Defaults to None.
+
+        Returns:
+            Order: The newly created Order object.
+
+        Raises:
+            ValueError: If a price is not positive in a book that does not allow
+                negative prices, if the quantity is not positive or violates the
+                book's size limits, or if `strict` is set and the price is not a
+                multiple of the tick size.
+        """
+        ...
+
+    def create_iceberg_order(
+        self, side: OrderType, price: float, quantity: float, display_quantity: float
+    ) -> Order:
+        """Creates a new iceberg order that only displays part of its quantity.
+
+        When the visible slice is consumed, it is replenished from the hidden
+        reserve and re-queued at the back of its price level.
+
+        Args:
+            side (OrderType): The side of the order (Buy or Sell).
+            price (float): The price of the order in floating point.
+            quantity (float): The total quantity of the instrument to trade.
+            display_quantity (float): The quantity visible on the book at a time.
+
+        Returns:
+            Order: The newly created Order object.
+        """
+        ...
+
+    def create_market_order(
+        self,
+        side: OrderType,
+        quantity: float,
+        reduce_only: bool = False,
+        max_slippage_ticks: Optional[int] = None,
+    ) -> Order:
+        """Creates a new market order that trades at any resting price.
+
+        Any unfilled remainder of a market order is canceled rather than rested.
+
+        With `max_slippage_ticks` set, the order is collared: it only trades within
+        that many ticks of the opposite touch (the best ask for a buy, the best bid
+        for a sell) as it stands when the order starts matching, which is when it
+        is added or, for a stop, when it triggers. The sweep stops at the first
+        level beyond the collar and the remainder is canceled.
+
+        Args:
+            side (OrderType): The side of the order (Buy or Sell).
+            quantity (float): The quantity of the instrument to trade.
+            reduce_only (bool, optional): If True, the order is trimmed when added so
+                that it can only reduce its owner's position. Defaults to False.
+            max_slippage_ticks (Optional[int], optional): How many ticks past the
+                opposite touch the order may trade. Defaults to None (no collar).
+
+        Returns:
+            Order: The newly created Order object.
+
+        Raises:
+            OrderRejectedError: If `quantity` is not positive or
+                `max_slippage_ticks` is negative.
+        """
+        ...
+
+    def create_stop_order(
+        self,
+        side: OrderType,
+        stop_price: float,
+        quantity: float,
+        limit_price: Optional[float] = None,
+    ) -> Order:
+        """Creates a new stop order that stays dormant until triggered.
+
+        Buy stops trigger when the last trade price rises to or above `stop_price`,
+        sell stops when it falls to or below. Once triggered, the order becomes a
+        market order (stop-loss), or a limit order at `limit_price` (stop-limit).
+
+        Args:
+            side (OrderType): The side of the order (Buy or Sell).
+            stop_price (float): The last trade price that triggers the order.
+            quantity (float): The quantity of the instrument to trade.
+            limit_price (Optional[float], optional): The limit price once triggered.
+                Defaults to None, which creates a stop-loss.
+
+        Returns:
+            Order: The newly created Order object.
+        """
+        ...
+
+    def create_peg_order(
+        self,
+        side: OrderType,
+        reference: PegReference,
+        quantity: float,
+        offset_ticks: int = 0,
+    ) -> Order:
+        """Creates a new pegged limit order whose price tracks a reference price.
+
+        The price is set to the reference plus `offset_ticks` when the order is
+        added, and updated by `reprice_pegs`.
+
+        Args:
+            side (OrderType): The side of the order (Buy or Sell).
+            reference (PegReference): The price the order tracks.
+            quantity (float): The quantity of the instrument to trade.
+            offset_ticks (int, optional): The distance in ticks from the reference.
+                Defaults to 0.
+
+        Returns:
+            Order: The newly created Order object.
+        """
+        ...
+
+    def reprice_pegs(self) -> List[Fill]:
+        """Moves every resting pegged order to its reference price plus its offset.
+
+        Pegs are repriced oldest first. A peg whose price changes is re-queued at the
+        back of its new level and matched if it now crosses the book; one whose price
+        is unchanged keeps its priority. A peg whose reference side is empty stays
+        where it is, as does one whose new price `add` would reject: outside the
+        price band, off the tick multiple, or, under `CrossedBookPolicy.Reject`,
+        leaving the book locked or crossed. Under `Resolve`, a crossed book is
+        resolved as after an add. Call this after the market moves.
+
+        Returns:
+            List[Fill]: The fills produced by pegs that crossed the book.
+        """
+        ...
+
+    def start_auction(self) -> None:
+        """Starts an auction, collecting orders without matching until `uncross`.
+
+        The book may become crossed while the auction runs. Only GTC limit orders are
+        accepted; stop orders are held as usual.
+        """
+        ...
+
+    def uncross(self) -> Tuple[Optional[float], List[Fill]]:
+        """Ends any auction and crosses the book at a single clearing price.
+
+        The clearing price is the one that maximizes the executed quantity. When
+        several prices execute the same quantity, the one leaving the smallest
+        imbalance between the buy and sell quantity at that price is used, then the
+        one closest to the last trade price, then the lowest. Orders with a
+        `matching_price` are counted at it rather than at their display price.
+
+        Every crossing order then trades at the clearing price, matching bids and
+        asks in price-time priority. For self-trade prevention and the fills'
+        aggressor side, the later of each matched pair is treated as the incoming
+        order. Continuous matching resumes afterwards.
+
+        Returns:
+            Tuple[Optional[float], List[Fill]]: The clearing price and the fills, or
+            None and no fills if the book does not cross.
+        """
         ...
 
     def add(self, order: Order) -> List[Fill]:
         """Adds an order to the book, matching it against existing orders if possible.
 
+        Any unfilled remainder rests on the book for GTC orders and is canceled
+        for IOC orders. FOK orders execute only if they can be filled in full,
+        otherwise no fills occur and the book is left untouched. Stop orders are
+        held until triggered, and the fills of any stops triggered by this order
+        are included in the result. Pegged orders are priced from their reference.
+        Canceled and expired orders are no longer tracked, so their IDs may be added
+        again.
+
+        Args:
+            order (Order): The order to add to the book.
+
+        Returns:
+            List[Fill]: A list of Fill objects created by matching this order.
+
+        Raises:
+            ValueError: If an order with the same ID is still tracked (resting, a
+                pending stop, or filled), if a post-only order would immediately
+                match, if the order's quantity violates the book's size limits, if
+                a pegged order's reference price is unavailable, if a limit price is
+                not a multiple of the book's tick multiple, if a limit price is not
+                positive in a book without negative prices, or if a market, IOC or
+                FOK order is added during an auction.
+            PriceBandError: If a limit order is priced outside the price band.
+        """
+        ...
+
+    def add_detailed(self, order: Order) -> Tuple[List[Fill], Optional[Order]]:
+        """Adds an order exactly like `add`, also returning what is left of it.
+
+        Args:
+            order (Order): The order to add.
+
+        Returns:
+            Tuple[List[Fill], Optional[Order]]: The fills, and the order's open
+            remainder as it now rests on the book (or waits as an untriggered stop
+            order), or None if it was fully filled or canceled.
+
+        Raises:
+            ValueError: For the same reasons as `add`.
+        """
+        ...
+
+    def add_summary(self, order: Order) -> Tuple[float, float, int]:
+        """Adds an order exactly like `add`, returning only a summary of its fills.
+
+        No Fill objects are created, which makes this cheaper than `add` in loops
+        that only need aggregate numbers.
+
+        Args:
+            order (Order): The order to add.
+
+        Returns:
+            Tuple[float, float, int]: The total filled quantity, the volume-weighted
+            average fill price (0.0 if nothing filled) and the number of fills.
+
+        Raises:
+            ValueError: For the same reasons as `add`.
+        """
+        ...
+
+    def add_by_level(self, order: Order) -> List[Tuple[float, List[Fill]]]:
+        """Adds an order exactly like `add`, grouping its fills by execution price.
+
+        Groups are listed in the order the levels were consumed, and fills keep their
+        execution order within each group.
+
+        Args:
+            order (Order): The order to add.
+
+        Returns:
+            List[Tuple[float, List[Fill]]]: The (price, fills) pair of each level
+            the order traded at.
+
+        Raises:
+            ValueError: For the same reasons as `add`.
+        """
+        ...
+
+    def add_many(self, orders: List[Order]) -> List[List[Fill]]:
+        """Adds several orders in sequence with a single call.
+
+        Each order is processed exactly as by `add`, matching against the book as
+        left by the orders before it.
+
+        Args:
+            orders (List[Order]): The orders to add, in submission order.
+
+        Returns:
+            List[List[Fill]]: The fills produced by each order, in the same order.
+
+        Raises:
+            ValueError: If an order is rejected by `add`. Orders before it have
+                already been applied and later orders are not processed.
+        """
+        ...
+
+    def add_group(self, orders: List[Order], all_or_none: bool = False) -> List[Fill]:
+        """Adds a group of linked orders, such as the legs of a spread.
+
+        Legs are evaluated and added in list order, each matching against the book
+        as left by the legs before it. With `all_or_none` set, the legs are first
+        added to a scratch copy of the book, where the order filter vets each of
+        them, and the group is committed only if every leg passes and fills in full
+        there; otherwise no fills occur and the book is left untouched.
+
+        Args:
+            orders (List[Order]): The legs to add, in evaluation order.
+            all_or_none (bool, optional): If True, either every leg fills in full or
+                none is added. Defaults to False (legs are added independently).
+
+        Returns:
+            List[Fill]: Every fill produced by the group, in execution order.
+
+        Raises:
+            ValueError: If a leg is rejected by `add`. With `all_or_none` set nothing
+                has been applied; otherwise the legs before it have been applied.
+        """
+        ...
+
+    def merge(self, other: "OrderBook") -> List[Fill]:
+        """Merges another book into this one through the normal matching path.
+
+        Each resting order and untriggered stop in `other` is added, oldest first,
+        exactly as by `add`, so orders that cross this book trade. Orders keep their
+        IDs and remaining quantities, and `other` is left unchanged.
+
+        Args:
+            other (OrderBook): The book whose orders are added to this one.
+
+        Returns:
+            List[Fill]: Every fill produced by the merge.
+
+        Raises:
+            ValueError: If the tick sizes differ or an order ID in `other` is already
+                tracked by this book, in which case nothing is added. Also raised if
+                an order is rejected by `add`; orders before it have already been
+                applied.
+        """
+        ...
+
+    def seed(self, orders: List[Order]) -> None:
+        """Places orders directly on the book as resting orders, without matching.
+
+        This rebuilds a book from a known, uncrossed state much faster than `add`.
+        Each order keeps its ID and timestamp, and orders at the same price queue in
+        list order. The orders are logged as adds, so replaying the event log
+        rebuilds the same book.
+
+        Args:
+            orders (List[Order]): The limit orders to place.
+
+        Raises:
+            ValueError: If an order is not an open GTC limit order (e.g. a market,
+                stop or pegged order), if an order ID is already tracked or
+                repeated, if a quantity breaks the book's size limits, if a price is
+                not positive in a book without negative prices, is outside the price
+                band or is not a multiple of the book's tick multiple, or if the
+                orders would leave the book locked or crossed outside an auction.
+                Everything is checked before any order is placed.
+        """
+        ...
+
+    def diff(self, other: "OrderBook") -> "BookDiff":
+        """Compares the orders tracked by this book with those tracked by another.
+
+        Orders are matched by ID and reported as modified when their remaining
+        quantity (including any hidden iceberg reserve) or status differ. This is
+        useful for finding where a replayed book diverges from a recorded one.
+
+        Args:
+            other (OrderBook): The book to compare against.
+
+        Returns:
+            BookDiff: The sorted IDs of added, removed and modified orders.
+        """
+        ...
+
+    def add_at(self, order: Order, timestamp: int) -> List[Fill]:
+        """Adds an order to the book exactly like `add`, with a fixed fill timestamp.
+
+        Every resulting Fill is stamped with `timestamp` instead of the wall-clock
+        time, which makes simulations and tests reproducible.
+
+        Args:
+            order (Order): The order to add to the book.
+            timestamp (int): The fill time in nanoseconds since the Unix epoch.
+
+        Returns:
+            List[Fill]: A list of Fill objects created by matching this order.
+        """
+        ...
+
+    def simulate(self, order: Order) -> List[Fill]:
+        """Returns the fills an order would produce if it were added now.
+
+        The order is matched exactly as `add` would match it, but against a copy of
+        the opposite side of the book, so the book itself is left untouched. Stop
+        orders that the simulated fills would trigger are not simulated.
+
+        Args:
+            order (Order): The order to simulate.
+
+        Returns:
+            List[Fill]: The hypothetical fills.
+
+        Raises:
+            ValueError: If `add` would reject the order.
+        """
+        ...
+
+    def set_on_fill(self, callback: Optional[Callable[[Fill], object]]) -> None:
+        """Registers a callback to be called with each fill produced by `add`.
+
+        The callback is invoked once per fill, in execution order, including fills
+        from triggered stops, after the book has been fully updated. It may read
+        the book, e.g. its best prices, but must not modify it. Simulated and
+        replayed matches never invoke it, and it is not included in snapshots.
+
+        If the callback raises, the remaining callbacks for that add are skipped and
+        the exception propagates out of `add`; the order has already been applied.
+
+        Args:
+            callback (Optional[Callable[[Fill], object]]): The function to call, or
+                None to remove the current callback.
+        """
+        ...
+
+    def set_order_filter(self, predicate: Optional[Callable[[Order], bool]]) -> None:
+        """Registers a predicate that vets each incoming order before it is applied.
+
+        The predicate is called with the order once the book's own checks have
+        passed. It may read the book but must not modify it. If it returns a falsy
+        value, `add` raises an `OrderRejectedError` with reason `FilteredOut` and
+        the book is left unchanged. If it raises, the exception propagates out of
+        `add` and the order is not applied. Replayed adds never invoke it, and it
+        is not included in snapshots.
+
+        Args:
+            predicate (Optional[Callable[[Order], bool]]): The function to call, or
+                None to remove the current filter.
+        """
+        ...
+
+    def cancel(self, order_id: str) -> Optional[Order]:
+        """Cancels an existing order if it is still open.
+
+        Args:
+            order_id (str): The ID of the order to cancel.
+
+        Returns:
+            Optional[Order]: The canceled order in its final state (with status
+            Canceled and its remaining quantity), or None if it was not found or
+            could not be canceled; `cancel_detailed` reports which.
+        """
+        ...
+
+    def cancel_detailed(self, order_id: str) -> Tuple[CancelOutcome, Optional[Order]]:
+        """Cancels an order like `cancel`, also reporting the outcome.
+
+        Before anything is removed, the order is checked to be queued at the price
+        level recorded for it (or to be a pending stop). If it is not, the book is
+        left unchanged so the inconsistency surfaces.
+
+        Args:
+            order_id (str): The ID of the order to cancel.
+
+        Returns:
+            Tuple[CancelOutcome, Optional[Order]]: The outcome and the order: its
+            final state if `Canceled`, its tracked copy if `FoundButNotInQueue`,
+            or None if `NotFound`.
+        """
+        ...
+
+    def clear(self) -> None:
+        """Resets the order book in place to the state of a freshly constructed book.
+
+        Every resting and stop order, retained fill, last trade and logged event is
+        dropped, while the tick size, self-trade prevention policy, matching mode,
+        fill callback and order filter are kept. To only discard the fill history,
+        use `drain_fills`.
+        """
+        ...
+
+    def cancel_many(self, order_ids: List[str]) -> List[str]:
+        """Cancels several orders in a single call.
+
+        Args:
+            order_ids (List[str]): The IDs of the orders to cancel.
+
+        Returns:
+            List[str]: The IDs that were found and canceled. Unknown IDs are skipped.
+        """
+        ...
+
+    def cancel_all(self, side: Optional[OrderType] = None) -> int:
+        """Cancels every resting and untriggered stop order on one or both sides.
+
+        Args:
+            side (Optional[OrderType], optional): The side to cancel. Defaults to
+                None, which cancels both sides.
+
+        Returns:
+            int: The number of orders canceled.
+        """
+        ...
+
+    def expire(self, now: int) -> List[Order]:
+        """Cancels every resting or stop order whose expiry is at or before `now`.
+
+        Expiries are kept in a min-heap, so only the orders that have expired are
+        visited and the cost does not grow with the size of the book.
+
+        Args:
+            now (int): The current time in nanoseconds since the Unix epoch.
+
+        Returns:
+            List[Order]: The expired orders in expiry order, marked as canceled.
+        """
+        ...
+
+    def position(self, owner: str) -> float:
+        """Returns the net filled quantity of an owner.
+
+        The position is the quantity bought minus the quantity sold across every
+        fill since the book was created or cleared. When a reduce-only order is
+        added, it is trimmed to this position less the owner's other reduce-only
+        orders already working on the same side, and rejected if nothing is left.
+
+        Args:
+            owner (str): The account to look up.
+
+        Returns:
+            float: The owner's net position, positive when long.
+        """
+        ...
+
+    def orders_by_owner(self, owner: str) -> List[Order]:
+        """Returns every open order belonging to an owner.
+
+        Both resting orders and untriggered stop orders are included. The orders are
+        copies, so modifying them does not affect the book.
+
+        Args:
+            owner (str): The owner to look up.
+
+        Returns:
+            List[Order]: The owner's open orders, oldest first.
+        """
+        ...
+
+    def quantity_at(self, side: OrderType, price: float) -> float:
+        """Returns the total visible quantity resting at a single price level.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+            price (float): The price of the level, converted to ticks like `create_order`.
+
+        Returns:
+            float: The level's total quantity, or 0 if the level is empty.
+        """
+        ...
+
+    def orders_at(self, side: OrderType, price: float) -> int:
+        """Returns the number of orders resting at a single price level.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+            price (float): The price of the level, converted to ticks like `create_order`.
+
+        Returns:
+            int: The number of orders at the level, or 0 if the level is empty.
+        """
+        ...
+
+    def order_ids_at(self, side: OrderType, price: float) -> List[str]:
+        """Lists the IDs of the orders resting at a single price level.
+
+        IDs are in time priority, the order in which FIFO matching with
+        `LevelPriority.Time` consumes them.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+            price (float): The price of the level, converted to ticks like `create_order`.
+
+        Returns:
+            List[str]: The order IDs at the level, or an empty list if the level is empty.
+        """
+        ...
+
+    def num_levels(self, side: OrderType) -> int:
+        """Returns the number of occupied price levels on one side of the book.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+
+        Returns:
+            int: The number of distinct prices with resting orders.
+        """
+        ...
+
+    def num_orders(self, side: OrderType) -> int:
+        """Returns the number of orders resting on one side of the book.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+
+        Returns:
+            int: The number of individual resting orders across all levels.
+        """
+        ...
+
+    def available_liquidity(self, side: OrderType, limit_ticks: int) -> float:
+        """Sums the resting quantity an incoming order could match against.
+
+        Hidden iceberg reserves are included since they are executable.
+
+        Args:
+            side (OrderType): The side of the incoming order.
+            limit_ticks (int): The worst acceptable price, in ticks.
+
+        Returns:
+            float: The total opposite-side quantity at acceptable prices.
+        """
+        ...
+
+    def liquidity_within(self, side: OrderType, price_limit: float) -> float:
+        """Sums the resting quantity an aggressor could trade up to a price limit.
+
+        This is `available_liquidity` with the limit given as a price. Hidden
+        iceberg reserves are included since they are executable.
+
+        Args:
+            side (OrderType): The side of the aggressor.
+            price_limit (float): The worst acceptable price, converted to ticks like
+                `create_order`.
+
+        Returns:
+            float: The total opposite-side quantity at or better than the limit, or
+                0.0 if the opposite side is empty.
+        """
+        ...
+
+    def impact_price(self, side: OrderType, quantity: float) -> Optional[float]:
+        """Calculates the average price to immediately trade a given quantity.
+
+        Walks the opposite side of the book from the touch without mutating it.
+        Hidden iceberg reserves are included since they are executable.
+
+        Args:
+            side (OrderType): The side of the hypothetical incoming order.
+            quantity (float): The quantity to trade.
+
+        Returns:
+            Optional[float]: The volume-weighted average execution price, or None
+            if the book cannot fill the whole quantity.
+        """
+        ...
+
+    def modify_quantity(
+        self, order_id: str, new_quantity: float, reject_increase: bool = False
+    ) -> bool:
+        """Changes the quantity of a resting order.
+
+        Reducing the quantity keeps the order's time priority, while increasing it
+        moves the order to the back of its price level.
+
+        Args:
+            order_id (str): The ID of the order to modify.
+            new_quantity (float): The new quantity of the order.
+            reject_increase (bool, optional): Whether to refuse a quantity increase
+                instead of moving the order to the back of its level, for callers
+                that treat any size increase as a new order. Defaults to False.
+
+        Returns:
+            bool: True if the order was modified, False if it is not resting.
+
+        Raises:
+            ValueError: If `new_quantity` is not positive, if the new total with any
+                hidden reserve violates the book's size limits, or if it is an
+                increase and `reject_increase` is set.
+        """
+        ...
+
+    def reduce(self, order_id: str, amount: float) -> Order:
+        """Pulls part of a resting order's size without canceling it.
+
+        The order keeps its time priority, and an iceberg's hidden reserve is
+        reduced before its visible quantity. If nothing is left, the order is
+        canceled instead.
+
+        Args:
+            order_id (str): The ID of the order to reduce.
+            amount (float): The quantity to pull from the order.
+
+        Returns:
+            Order: The updated order, or the canceled order if nothing is left.
+
+        Raises:
+            OrderRejectedError: If `amount` is not positive (or fractional in a book
+                with integer quantities), if the order is not resting, or if
+                `amount` exceeds the order's remaining quantity.
+        """
+        ...
+
+    def replace(
+        self,
+        order_id: str,
+        new_price: float,
+        new_quantity: float,
+        reject_increase: bool = False,
+    ) -> Order:
+        """Replaces a resting order using cancel-replace semantics.
+
+        A quantity decrease at the same price keeps the order's time priority. Any
+        price change or quantity increase cancels the old order and submits it
+        afresh (same ID, new timestamp, back of the new level), which may
+        immediately match. A pegged order moved to a new price becomes a plain limit
+        order.
+
+        Args:
+            order_id (str): The ID of the order to replace.
+            new_price (float): The new price of the order in floating point.
+            new_quantity (float): The new quantity of the order.
+            reject_increase (bool, optional): Whether to refuse a quantity increase,
+                leaving the original order untouched. Defaults to False.
+
+        Returns:
+            Order: The resulting order after the replace.
+
+        Raises:
+            ValueError: If the order is not resting, the new price or quantity is
+                not positive, the quantity increases while `reject_increase` is
+                set, or `add` would reject the replacement (in which case the
+                original order is left resting in its place in the queue).
+        """
+        ...
+
+    def get_order(self, order_id: str) -> Optional[Order]:
+        """Retrieves an order by its ID.
+
+        Args:
+            order_id (str): The ID of the order to retrieve.
+
+        Returns:
+            Optional[Order]: The matching Order if found, otherwise None.
+        """
+        ...
+
+    def queue_position(self, order_id: str) -> Optional[Tuple[int, float]]:
+        """Returns where a resting order sits within its price level.
+
+        Args:
+            order_id (str): The ID of the order to locate.
+
+        Returns:
+            Optional[Tuple[int, float]]: The zero-based index of the order within its
+                price level and the displayed quantity of the orders ahead of it,
+                or None if the order is not resting.
+        """
+        ...
+
+    def iter_side(self, side: OrderType) -> "OrderIterator":
+        """Iterates lazily over the resting orders on one side of the book.
+
+        Orders are yielded in price-time priority (best price first, oldest first
+        within a level) and copied one at a time, so scanning a large book does not
+        clone every order up front.
+
+        Args:
+            side (OrderType): The side of the book to iterate.
+
+        Returns:
+            OrderIterator: An iterator over the resting orders.
+        """
+        ...
+
+    def top_orders(self, side: OrderType, n: int) -> List[Order]:
+        """Returns the first `n` resting orders on one side of the book.
+
+        Orders are returned in price-time priority, and only the returned orders are
+        copied.
+
+        Args:
+            side (OrderType): The side of the book to read.
+            n (int): The maximum number of orders to return.
+
+        Returns:
+            List[Order]: Up to `n` orders, best price first and oldest first within a
+            level.
+        """
+        ...
+
+    @property
+    def buy_orders(self) -> List[Order]:
+        """List[Order]: The list of active buy orders in the book."""
+        ...
+
+    @property
+    def sell_orders(self) -> List[Order]:
+        """List[Order]: The list of active sell orders in the book."""
+        ...
+
+    @property
+    def stop_orders(self) -> List[Order]:
+        """List[Order]: The list of untriggered stop orders, in submission order."""
+        ...
+
+    @property
+    def stp_policy(self) -> SelfTradePrevention:
+        """SelfTradePrevention: How matches between orders with the same owner are prevented."""
+        ...
+
+    @property
+    def min_quantity(self) -> Optional[float]:
+        """Optional[float]: The smallest accepted order quantity, or None if unconstrained."""
+        ...
+
+    @property
+    def max_quantity(self) -> Optional[float]:
+        """Optional[float]: The largest accepted order quantity, or None if unconstrained."""
+        ...
+
+    @property
+    def buy_notional(self) -> float:
+        """float: The total resting buy notional, summing price times visible quantity."""
+        ...
+
+    @property
+    def sell_notional(self) -> float:
+        """float: The total resting sell notional, summing price times visible quantity."""
+        ...
+
+    def avg_price(self, side: OrderType) -> Optional[float]:
+        """Returns the size-weighted average price of the resting orders on one side.
+
+        Every level is included, weighting each price by its visible quantity.
+
+        Args:
+            side (OrderType): The side of the book to value.
+
+        Returns:
+            Optional[float]: The side's notional divided by its visible quantity, or
+            None if the side is empty.
+        """
+        ...
+
+    @property
+    def price_band_ticks(self) -> Optional[int]:
+        """Optional[int]: How far, in ticks, limit prices may be from the last trade price, or None if unbanded."""
+        ...
+
+    @property
+    def lot_size(self) -> Optional[float]:
+        """Optional[float]: The size order quantities must be a multiple of, or None if unconstrained."""
+        ...
+
+    @property
+    def fill_price_rule(self) -> FillPriceRule:
+        """FillPriceRule: Which price a match between two orders trades at."""
+        ...
+
+    @property
+    def in_auction(self) -> bool:
+        """bool: Whether the book is collecting orders for an auction."""
+        ...
+
+    @property
+    def maker_fee_bps(self) -> float:
+        """float: The fee charged to the resting side of each fill, in basis points."""
+        ...
+
+    @property
+    def taker_fee_bps(self) -> float:
+        """float: The fee charged to the incoming side of each fill, in basis points."""
+        ...
+
+    @property
+    def max_orders(self) -> Optional[int]:
+        """Optional[int]: The most orders that may rest on the book, or None if unbounded."""
+        ...
+
+    @property
+    def crossed_book_policy(self) -> CrossedBookPolicy:
+        """CrossedBookPolicy: How an add that would leave the book crossed is handled."""
+        ...
+
+    @property
+    def allow_negative_prices(self) -> bool:
+        """bool: Whether orders may have zero or negative prices."""
+        ...
+
+    @property
+    def tick_multiple(self) -> int:
+        """int: The number of ticks that limit prices must be a multiple of."""
+        ...
+
+    @property
+    def quantity_is_integer(self) -> bool:
+        """bool: Whether every order quantity must be a whole number."""
+        ...
+
+    @property
+    def level_priority(self) -> LevelPriority:
+        """LevelPriority: Which resting order at a price level is matched first."""
+        ...
+
+    @property
+    def matching_mode(self) -> MatchingMode:
+        """MatchingMode: How an incoming order is allocated across a price level."""
+        ...
+
+    @property
+    def self_trade_canceled(self) -> List[Order]:
+        """List[Order]: The orders canceled by self-trade prevention during the most recent add."""
+        ...
+
+    @property
+    def evicted(self) -> List[Order]:
+        """List[Order]: The orders evicted by the `max_orders` cap during the most recent add."""
+        ...
+
+    @property
+    def last_trade_price(self) -> Optional[float]:
+        """Optional[float]: The price of the most recent fill, or None if nothing has traded."""
+        ...
+
+    @property
+    def last_trade_quantity(self) -> Optional[float]:
+        """Optional[float]: The quantity of the most recent fill, or None if nothing has traded."""
+        ...
+
+    @property
+    def last_trade_timestamp(self) -> Optional[int]:
+        """Optional[int]: The timestamp of the most recent fill, or None if nothing has traded."""
+        ...
+
+    @property
+    def tick_size(self) -> float:
+        """float: The minimum price increment for orders in the book."""
+        ...
+
+    def ticks_to_price(self, ticks: int) -> float:
+        """Converts a price in ticks to a price.
+
+        The tick size is held as an exact decimal, so the result is the closest float
+        to the true price (201 ticks of 0.05 is exactly 10.05). Every price the book
+        reports is converted this way.
+
         Args:
-            order (Order): The order to add to the book.
+            ticks (int): The price in ticks.
 
         Returns:
-            List[Fill]: A list of Fill objects created by matching this order.
+            float: The price.
         """
         ...
 
-    def cancel(self, order_id: str) -> bool:
-        """Cancels an existing order if it is still open.
+    def price_to_ticks(self, price: float) -> int:
+        """Converts a price to the nearest whole number of ticks.
+
+        Every price passed to the book, such as in `create_order`, is converted this
+        way.
 
         Args:
-            order_id (str): The ID of the order to cancel.
+            price (float): The price.
 
         Returns:
-            bool: True if the order was successfully canceled, False otherwise.
+            int: The price in ticks.
         """
         ...
 
-    def get_order(self, order_id: str) -> Optional[Order]:
-        """Retrieves an order by its ID.
+    def depth(
+        self, n: int
+    ) -> Tuple[List[Tuple[float, float]], List[Tuple[float, float]]]:
+        """Returns the top `n` aggregated price levels on each side of the book.
 
         Args:
-            order_id (str): The ID of the order to retrieve.
+            n (int): The maximum number of levels to return per side.
 
         Returns:
-            Optional[Order]: The matching Order if found, otherwise None.
+            Tuple[List[Tuple[float, float]], List[Tuple[float, float]]]: The bid and
+            ask levels as (price, total_quantity) pairs, with bids in descending and
+            asks in ascending price order.
         """
         ...
 
-    @property
-    def buy_orders(self) -> List[Order]:
-        """List[Order]: The list of active buy orders in the book."""
+    def level(self, side: OrderType, depth: int) -> Optional[Tuple[float, float]]:
+        """Returns a single aggregated price level, counted from the touch.
+
+        Unlike `depth`, the levels in front of it are not built.
+
+        Args:
+            side (OrderType): The side of the book to inspect.
+            depth (int): How many levels from the touch, where 0 is the best level.
+
+        Returns:
+            Optional[Tuple[float, float]]: The level's (price, total_quantity), or None
+            if the side has no more than `depth` levels.
+        """
+        ...
+
+    def ladder(self) -> Tuple[Dict[float, float], Dict[float, float]]:
+        """Returns every aggregated price level on each side of the book as a dict.
+
+        Returns:
+            Tuple[Dict[float, float], Dict[float, float]]: The bid and ask ladders,
+            each mapping price to total quantity, with bids in descending and asks in
+            ascending price order.
+        """
+        ...
+
+    def cumulative_depth(self, side: OrderType) -> List[Tuple[float, float]]:
+        """Returns the cumulative depth curve of one side of the book.
+
+        Args:
+            side (OrderType): The side of the book to accumulate.
+
+        Returns:
+            List[Tuple[float, float]]: (price, cumulative_quantity) pairs from the touch
+            outward, where each quantity includes every better-priced level. Bids run
+            down in price and asks up. Empty if the side has no orders.
+        """
+        ...
+
+    def quantity_histogram(self, bucket_ticks: int) -> List[Tuple[int, float, float]]:
+        """Returns the resting quantity grouped into fixed-width price buckets.
+
+        A bucket covers the ticks from its start up to the next multiple of
+        `bucket_ticks`. Empty buckets are skipped.
+
+        Args:
+            bucket_ticks (int): The width of each bucket in ticks.
+
+        Returns:
+            List[Tuple[int, float, float]]: (start_tick, buy_quantity, sell_quantity)
+            triples in ascending price order.
+
+        Raises:
+            ValueError: If `bucket_ticks` is not positive.
+        """
         ...
 
     @property
-    def sell_orders(self) -> List[Order]:
-        """List[Order]: The list of active sell orders in the book."""
+    def best_bid(self) -> Optional[Tuple[float, float]]:
+        """Optional[Tuple[float, float]]: The best (highest) bid as a (price, total_quantity)
+        pair summed across every order at that level, or None if there are no bids."""
         ...
 
     @property
-    def tick_size(self) -> float:
-        """float: The minimum price increment for orders in the book."""
+    def best_ask(self) -> Optional[Tuple[float, float]]:
+        """Optional[Tuple[float, float]]: The best (lowest) ask as a (price, total_quantity)
+        pair summed across every order at that level, or None if there are no asks."""
+        ...
+
+    def mid_price(self) -> Optional[float]:
+        """Calculates the mid-price between the best bid and best ask.
+
+        Returns:
+            Optional[float]: The mid-price if both buy and sell orders exist,
+            otherwise None.
+        """
+        ...
+
+    def is_crossed(self) -> bool:
+        """Checks whether the best bid is priced above the best ask.
+
+        Matching never leaves the book crossed outside an auction, so a crossed book
+        indicates a bug. Only the two best levels are compared.
+
+        Returns:
+            bool: True if the best bid is above the best ask, False otherwise.
+        """
+        ...
+
+    def quote(self) -> Optional["Quote"]:
+        """Returns the current best bid and offer as a single quote.
+
+        Sizes are the total quantity at each price, and the quote is stamped with
+        the wall-clock time.
+
+        Returns:
+            Optional[Quote]: The quote, or None if both sides are empty. On a
+            one-sided book, the empty side has a price of None and a size of 0.0.
+        """
+        ...
+
+    def microprice(self) -> Optional[float]:
+        """Calculates the size-weighted mid-price (microprice).
+
+        Computed as `(bid * ask_size + ask * bid_size) / (bid_size + ask_size)` from
+        the best bid and ask and their total level quantities, so it leans toward the
+        side with less size.
+
+        Returns:
+            Optional[float]: The microprice if both buy and sell orders exist,
+            otherwise None.
+        """
         ...
 
     def spread(self) -> Optional[float]:
@@ -254,6 +2068,366 @@ class OrderBook:
         """
         ...
 
+    def spread_ticks(self) -> Optional[int]:
+        """Calculates the spread between the best buy and sell orders in ticks.
+
+        Unlike `spread`, this is exact, since no conversion to a price is needed.
+
+        Returns:
+            Optional[int]: The best ask minus the best bid in ticks if both buy and
+            sell orders exist, otherwise None.
+        """
+        ...
+
+    def stats(self) -> Dict[str, float]:
+        """Returns the book's activity counters.
+
+        The counters are `orders_added`, `orders_canceled` (including expiries),
+        `fills`, `volume` (the quantity traded), `times_emptied` (the number of
+        adds and cancels that left the book with no resting orders) and
+        `crosses_resolved` (the number of adds that left the book locked or crossed
+        until resolved). They run from
+        the book's creation, the last `clear` or the last `reset_stats`, and are
+        not included in snapshots.
+
+        Returns:
+            Dict[str, float]: The counters keyed by name.
+        """
+        ...
+
+    def reset_stats(self) -> None:
+        """Resets every counter reported by `stats` to zero."""
+        ...
+
+    def trade_count(self) -> int:
+        """Returns the number of fills the order book has produced.
+
+        Returns:
+            int: The number of fills.
+        """
+        ...
+
+    def total_traded_volume(self) -> float:
+        """Returns the total quantity traded across every fill the order book has produced.
+
+        Returns:
+            float: The total traded quantity.
+        """
+        ...
+
+    def fills_for_order(self, order_id: str) -> List[Fill]:
+        """Returns every fill in which an order took part, as buyer or seller.
+
+        This scans the whole trade history, so it is O(n) in the total number of
+        fills the book has produced.
+
+        Args:
+            order_id (str): The ID of the order to trace.
+
+        Returns:
+            List[Fill]: The order's fills in execution order, empty if it never
+                traded.
+        """
+        ...
+
+    def fills_to_csv(self) -> str:
+        """Exports every fill the order book has produced as CSV.
+
+        The output has a `quantity,price,buy_id,sell_id,timestamp` header and one line
+        per fill in execution order. Floats are written in their shortest form that
+        parses back to the exact same value. Order IDs containing a comma, quote or
+        line break are quoted per RFC 4180, so the output reads back with `csv`.
+
+        Returns:
+            str: The fills as CSV text.
+        """
+        ...
+
+    def fills_to_columns(
+        self,
+    ) -> Tuple[List[float], List[float], List[str], List[str], List[int]]:
+        """Returns every fill the order book has produced as column lists.
+
+        This is a columnar alternative to iterating Fill objects, for example
+        `pandas.DataFrame(dict(zip(names, book.fills_to_columns())))`, that avoids
+        creating a Python object per fill.
+
+        Returns:
+            Tuple[List[float], List[float], List[str], List[str], List[int]]: The
+                quantity, price, buy_id, sell_id and timestamp columns, in
+                execution order.
+        """
+        ...
+
+    def drain_fills(self) -> List[Fill]:
+        """Removes and returns every retained fill, leaving the book itself intact.
+
+        Resting orders, the last trade and the event log are kept, so fills can be
+        moved into external storage periodically without the history growing
+        without bound. Methods that read the history, such as `trade_count` and
+        `fills_for_order`, only see fills produced after the last drain. Use
+        `clear` to wipe the book as well.
+
+        Returns:
+            List[Fill]: The drained fills, in execution order.
+        """
+        ...
+
+    def events(self) -> List[Event]:
+        """Returns every mutating operation applied to the book, in order.
+
+        Adds record the timestamp stamped on their fills, while cancels, cancel-alls
+        and expiries record one `Event.Cancel` per removed order. A `replace` is
+        recorded as the cancel, add or modify it performs.
+
+        Returns:
+            List[Event]: The event log.
+        """
+        ...
+
+    @staticmethod
+    def replay(
+        events: List[Event],
+        tick_size: float = 0.01,
+        stp_policy: SelfTradePrevention = SelfTradePrevention.CancelNewest,
+        matching_mode: MatchingMode = MatchingMode.Fifo,
+        fill_price_rule: FillPriceRule = FillPriceRule.Seller,
+        min_quantity: Optional[float] = None,
+        max_quantity: Optional[float] = None,
+        lot_size: Optional[float] = None,
+        price_band_ticks: Optional[int] = None,
+        level_priority: LevelPriority = LevelPriority.Time,
+        quantity_is_integer: bool = False,
+        maker_fee_bps: float = 0.0,
+        taker_fee_bps: float = 0.0,
+        max_orders: Optional[int] = None,
+        crossed_book_policy: CrossedBookPolicy = CrossedBookPolicy.Resolve,
+        tick_multiple: int = 1,
+        allow_negative_prices: bool = False,
+    ) -> "OrderBook":
+        """Rebuilds an order book by re-applying events to an empty book.
+
+        Adds are replayed with their recorded timestamps, so the rebuilt book and its
+        fills match the original run exactly when the same settings are used.
+
+        Args:
+            events (List[Event]): The events to apply, in order.
+            tick_size (float, optional): The minimum price increment. Defaults to 0.01.
+            stp_policy (SelfTradePrevention, optional): The self-trade prevention
+                policy. Defaults to CancelNewest.
+            matching_mode (MatchingMode, optional): The matching mode. Defaults to Fifo.
+            fill_price_rule (FillPriceRule, optional): The fill price rule. Defaults
+                to Seller.
+            min_quantity (Optional[float], optional): The minimum order quantity.
+                Defaults to None.
+            max_quantity (Optional[float], optional): The maximum order quantity.
+                Defaults to None.
+            lot_size (Optional[float], optional): The lot size. Defaults to None.
+            price_band_ticks (Optional[int], optional): The price band in ticks.
+                Defaults to None.
+            level_priority (LevelPriority, optional): The level priority. Defaults
+                to Time.
+            quantity_is_integer (bool, optional): Whether quantities must be whole
+                numbers. Defaults to False.
+            maker_fee_bps (float, optional): The maker fee rate. Defaults to 0.0.
+            taker_fee_bps (float, optional): The taker fee rate. Defaults to 0.0.
+            max_orders (Optional[int], optional): The cap on resting orders.
+                Defaults to None.
+            crossed_book_policy (CrossedBookPolicy, optional): The crossed book
+                policy. Defaults to Resolve.
+            tick_multiple (int, optional): The price grid in ticks. Defaults to 1.
+            allow_negative_prices (bool, optional): Whether zero and negative
+                prices are accepted. Defaults to False.
+
+        Returns:
+            OrderBook: The rebuilt order book.
+
+        Raises:
+            ValueError: If an event is rejected when re-applied.
+        """
+        ...
+
+    def to_json(self) -> str:
+        """Serializes the full state of the order book to JSON.
+
+        The snapshot covers resting and stop orders, tracked orders, the tick size,
+        and the book's matching settings.
+
+        Returns:
+            str: The JSON snapshot.
+        """
+        ...
+
+    @staticmethod
+    def from_json(data: str) -> "OrderBook":
+        """Restores an order book from a snapshot produced by `to_json`.
+
+        Args:
+            data (str): The JSON snapshot.
+
+        Returns:
+            OrderBook: The restored order book.
+
+        Raises:
+            ValueError: If the data is not a valid order book snapshot.
+        """
+        ...
+
+    def to_bytes(self) -> bytes:
+        """Serializes the full state of the order book to a compact binary snapshot.
+
+        This captures the same state as `to_json` but is much faster to produce and
+        restore, which makes it suitable for frequent checkpointing.
+
+        Returns:
+            bytes: The binary snapshot.
+        """
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> "OrderBook":
+        """Restores an order book from a snapshot produced by `to_bytes`.
+
+        Args:
+            data (bytes): The binary snapshot.
+
+        Returns:
+            OrderBook: The restored order book.
+
+        Raises:
+            ValueError: If the data is not a valid order book snapshot.
+        """
+        ...
+
+    def validate(self) -> bool:
+        """Audits the order book's internal consistency.
+
+        Checks that every price level is non-empty and holds open orders on the right
+        side and at the right price, that every queued order and pending stop order
+        matches its copy in the order lookup, that no order is queued twice, and that
+        every open order in the lookup is either queued or a pending stop.
+
+        Returns:
+            bool: True if every invariant holds, False otherwise.
+        """
+        ...
+
+    def compact(self) -> int:
+        """Repairs the order book's internal indexes.
+
+        The price level queues and pending stop orders are treated as the source of
+        truth: empty price levels are dropped, order lookup entries that differ from
+        their queued or pending copy are replaced, and open lookup entries that are
+        neither queued nor pending are removed. Filled and canceled entries are kept.
+        This is a no-op on a healthy book and takes O(n) time in the number of
+        tracked orders.
+
+        Returns:
+            int: The number of inconsistencies fixed.
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Returns the number of orders resting on the book.
+
+        Filled orders and untriggered stop orders are not counted.
+        """
+        ...
+
+    def __contains__(self, order_id: str) -> bool:
+        """Checks whether the order with `order_id` is resting on the book."""
+        ...
+
     def __repr__(self) -> str:
         """Returns a string representation of the OrderBook."""
         ...
+
+class OrderIterator:
+    """A lazy iterator over the resting orders on one side of an OrderBook.
+
+    The iterator keeps a cursor into the live book, so orders added or removed ahead
+    of the cursor while iterating are reflected in what it yields. The cursor
+    remembers the last order yielded, so canceling that order while iterating does
+    not skip the one behind it.
+    """
+
+    def __iter__(self) -> "OrderIterator": ...
+    def __next__(self) -> Order: ...
+
+class BookDiff:
+    """The differences between two order books, as reported by `OrderBook.diff`.
+
+    Orders are matched by ID and compared on remaining quantity and status.
+    """
+
+    @property
+    def added(self) -> List[str]:
+        """List[str]: The IDs of orders tracked only by the other book."""
+        ...
+
+    @property
+    def removed(self) -> List[str]:
+        """List[str]: The IDs of orders tracked only by this book."""
+        ...
+
+    @property
+    def modified(self) -> List[str]:
+        """List[str]: The IDs of orders whose remaining quantity or status differ."""
+        ...
+
+    def is_empty(self) -> bool:
+        """Checks whether the two books track the same orders in the same state.
+
+        Returns:
+            bool: True if no order was added, removed or modified.
+        """
+        ...
+
+class Quote:
+    """The best bid and offer of an order book at one moment, as reported by
+    `OrderBook.quote`.
+
+    Sizes are the total visible quantity at each price. On a one-sided book, the
+    empty side has a price of None and a size of 0.0.
+    """
+
+    @property
+    def bid_price(self) -> Optional[float]:
+        """Optional[float]: The best bid price, or None if there are no bids."""
+        ...
+
+    @property
+    def bid_size(self) -> float:
+        """float: The total quantity at the best bid."""
+        ...
+
+    @property
+    def ask_price(self) -> Optional[float]:
+        """Optional[float]: The best ask price, or None if there are no asks."""
+        ...
+
+    @property
+    def ask_size(self) -> float:
+        """float: The total quantity at the best ask."""
+        ...
+
+    @property
+    def timestamp(self) -> int:
+        """int: When the quote was taken, in nanoseconds since the epoch."""
+        ...
+
+    def to_json(self) -> str:
+        """Serializes the quote to JSON, e.g. for logging.
+
+        Returns:
+            str: The JSON representation of the quote.
+        """
+        ...
+
+    def __repr__(self) -> str:
+        """Returns a string representation of the quote.
+
+        Returns:
+            str: A string representation of the quote.
+        """
+        ...
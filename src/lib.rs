@@ -1,10 +1,167 @@
+use pyo3::create_exception;
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
 use serde::{Deserialize, Serialize};
 
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+create_exception!(
+    litebook,
+    OrderRejectedError,
+    pyo3::exceptions::PyValueError,
+    "Raised when an order or order change is rejected. The `reason` attribute holds the OrderRejectReason."
+);
+
+create_exception!(
+    litebook,
+    PriceBandError,
+    OrderRejectedError,
+    "Raised when an order is rejected for pricing outside the book's price band."
+);
+
+/// Returns the current wall-clock time in nanoseconds since the Unix epoch.
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos() as u64
+}
+
+/// How far, in ticks or lots, a value may sit from an exact multiple of the
+/// tick or lot size and still be accepted. Absorbs float error such as
+/// `10.05 / 0.05 == 201.00000000000003` without admitting off-grid values.
+const GRID_EPSILON: f64 = 1e-9;
+
+/// Remaining quantities smaller than this are treated as fully filled. Repeated
+/// float subtraction can otherwise leave dust such as `1e-16` that keeps an
+/// order open on the book forever.
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+/// A tick size held as an exact decimal, `num / 10^scale`, so that converting
+/// between ticks and prices never accumulates float error: 201 ticks of 0.05
+/// are computed as `201 * 5 / 100`, not `201.0 * 0.05`. Tick sizes without a
+/// decimal form of at most 15 places (such as 1/3) are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TickScale {
+    num: i64,
+    scale: u32,
+}
+
+impl TickScale {
+    const MAX_SCALE: u32 = 15;
+
+    /// Finds the shortest decimal form of `tick_size`, raising `ValueError` if it
+    /// is not positive and finite, has no exact form within `MAX_SCALE` places,
+    /// or is too large to scale into an `i64`.
+    fn new(tick_size: f64) -> PyResult<Self> {
+        if !(tick_size.is_finite() && tick_size > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "tick_size must be a positive, finite number, got {}",
+                tick_size
+            )));
+        }
+        let scaled = |scale: u32| tick_size * 10f64.powi(scale as i32);
+        let scale = (0..=Self::MAX_SCALE)
+            .find(|&scale| {
+                let value = scaled(scale);
+                value.round() >= 1.0 && (value - value.round()).abs() < GRID_EPSILON
+            })
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "tick_size {} has no exact decimal form of at most {} places",
+                    tick_size,
+                    Self::MAX_SCALE
+                ))
+            })?;
+        let num = scaled(scale).round();
+        if num >= i64::MAX as f64 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "tick_size {} is too large",
+                tick_size
+            )));
+        }
+        Ok(Self {
+            num: num as i64,
+            scale,
+        })
+    }
+
+    /// Returns `10^scale`, which is exact in an `f64`.
+    fn denominator(self) -> f64 {
+        10f64.powi(self.scale as i32)
+    }
+
+    /// Returns the tick size as a float.
+    fn tick_size(self) -> f64 {
+        self.num as f64 / self.denominator()
+    }
+
+    /// Converts a price in ticks to a price, rounding only once.
+    fn to_price(self, ticks: i64) -> f64 {
+        (ticks as i128 * self.num as i128) as f64 / self.denominator()
+    }
+
+    /// Converts a price to a (possibly fractional) number of ticks.
+    fn exact_ticks(self, price: f64) -> f64 {
+        price * self.denominator() / self.num as f64
+    }
+}
+
+/// An aggregated `(price, total_quantity)` pair for a single price level.
+type PriceLevel = (f64, f64);
+
+/// Fills as `(quantity, price, buy_id, sell_id, timestamp)` column vectors.
+type FillColumns = (Vec<f64>, Vec<f64>, Vec<String>, Vec<String>, Vec<u64>);
+
+/// Returns a well-mixed 64-bit hash of `key` and `step` (FNV-1a followed by the
+/// SplitMix64 finalizer). It is stable across runs and platforms, so choices
+/// derived from it are reproducible when events are replayed.
+fn stable_hash(key: &str, step: u64) -> u64 {
+    let mut x = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    x ^= step.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Returns the total visible quantity resting in a price level.
+fn level_quantity(queue: &VecDeque<Order>) -> f64 {
+    queue.iter().map(|order| order.quantity).sum()
+}
+
+/// Quotes a CSV field per RFC 4180 if it contains a comma, double quote or
+/// line break, doubling any double quotes inside it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Copies the state that matching changes (quantities and status) from `order`
+/// into its entry in `orders`, inserting a copy only if it is not yet tracked.
+/// Updating in place avoids cloning the order on every match.
+fn sync_fill_state(orders: &mut HashMap<String, Order>, order: &Order) {
+    match orders.get_mut(&order.id) {
+        Some(tracked) => {
+            tracked.quantity = order.quantity;
+            tracked.hidden_quantity = order.hidden_quantity;
+            tracked.filled_quantity = order.filled_quantity;
+            tracked.status = order.status.clone();
+        }
+        None => {
+            orders.insert(order.id.clone(), order.clone());
+        }
+    }
+}
+
 /// Represents the side of an order: either Buy or Sell.
 #[pyclass(eq, eq_int)]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -22,37 +179,387 @@ pub enum OrderStatus {
     Canceled,
 }
 
+/// Represents how long an order remains active before it is canceled.
+/// - `GTC` (good-till-canceled) rests on the book until filled or canceled.
+/// - `IOC` (immediate-or-cancel) matches what it can and cancels the remainder.
+/// - `FOK` (fill-or-kill) never rests; any unfilled remainder is canceled.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum TimeInForce {
+    GTC,
+    IOC,
+    FOK,
+}
+
+/// Represents how an order is priced.
+/// - `Limit` orders trade at their price or better.
+/// - `Market` orders trade at any price and never rest on the book.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderKind {
+    Limit,
+    Market,
+}
+
+/// Represents the price a pegged order tracks. Pegged orders are excluded when
+/// finding the reference, so a peg never follows itself or another peg.
+/// - `BestBid` tracks the best bid.
+/// - `BestAsk` tracks the best ask.
+/// - `Mid` tracks the midpoint of the best bid and ask, rounded away from the
+///   touch on the order's own side (down for buys, up for sells).
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PegReference {
+    BestBid,
+    BestAsk,
+    Mid,
+}
+
+/// Represents how a match between two orders with the same owner is prevented.
+/// - `CancelNewest` cancels the incoming order and leaves the resting order.
+/// - `CancelOldest` cancels the resting order and keeps matching the incoming order.
+/// - `CancelBoth` cancels both orders.
+/// - `DecrementBoth` reduces both orders by the smaller quantity without a fill,
+///   canceling any order that reaches zero.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelfTradePrevention {
+    CancelNewest,
+    CancelOldest,
+    CancelBoth,
+    DecrementBoth,
+}
+
+/// Represents how an incoming order is allocated across a price level.
+/// - `Fifo` fills resting orders in time priority, each in full before the next.
+/// - `ProRata` splits the traded quantity across every resting order at the
+///   level in proportion to its visible quantity. Allocations are not rounded
+///   (except down to whole units in integer books); the last order in the
+///   queue takes whatever remains of the traded quantity, so floating-point
+///   residue never leaves the level under- or over-filled.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchingMode {
+    Fifo,
+    ProRata,
+}
+
+/// Represents which resting order at the best price is matched first when the
+/// book uses `MatchingMode::Fifo`. Pro-rata matching ignores it.
+/// - `Time` takes the oldest order. A partially filled order keeps its place
+///   at the front, so the sequence is fully stable.
+/// - `Size` takes the order with the largest visible quantity, breaking ties
+///   by time priority. Partially filled orders keep their queue position, but
+///   may lose their turn once their remaining size is no longer the largest.
+/// - `Random` takes an order chosen pseudo-randomly from the level. No order
+///   is favored, but the choice is derived from the incoming order's ID, so
+///   replaying the same events reproduces the same matches.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelPriority {
+    Time,
+    Size,
+    Random,
+}
+
+impl LevelPriority {
+    /// Returns the queue index of the resting order to match next. `step`
+    /// counts the matches already made for `incoming`.
+    fn select(&self, queue: &VecDeque<Order>, incoming: &Order, step: u64) -> usize {
+        match self {
+            LevelPriority::Time => 0,
+            LevelPriority::Size => {
+                let mut best = 0;
+                for (index, order) in queue.iter().enumerate() {
+                    if order.quantity > queue[best].quantity {
+                        best = index;
+                    }
+                }
+                best
+            }
+            LevelPriority::Random => {
+                (stable_hash(&incoming.id, step) % queue.len() as u64) as usize
+            }
+        }
+    }
+}
+
+/// Represents how a price that is not a multiple of the tick size is rounded
+/// onto the grid. Prices within floating-point noise of a tick always snap to
+/// that tick.
+/// - `Nearest` rounds to the nearest tick, with halves rounded away from zero.
+/// - `Up` rounds to the tick above.
+/// - `Down` rounds to the tick below.
+/// - `TowardConservative` rounds buys down and sells up, so rounding never
+///   makes an order more aggressive.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+    TowardConservative,
+}
+
+impl RoundingMode {
+    /// Rounds `exact_ticks`, a price expressed in fractional ticks, for an
+    /// order on `side`.
+    fn round(&self, exact_ticks: f64, side: &OrderType) -> i64 {
+        let nearest = exact_ticks.round();
+        if (exact_ticks - nearest).abs() <= GRID_EPSILON {
+            return nearest as i64;
+        }
+        let rounded = match (self, side) {
+            (RoundingMode::Nearest, _) => nearest,
+            (RoundingMode::Up, _) | (RoundingMode::TowardConservative, OrderType::Sell) => {
+                exact_ticks.ceil()
+            }
+            (RoundingMode::Down, _) | (RoundingMode::TowardConservative, OrderType::Buy) => {
+                exact_ticks.floor()
+            }
+        };
+        rounded as i64
+    }
+}
+
+/// Represents why an order or order change was rejected. It is attached as the
+/// `reason` attribute of the `OrderRejectedError` raised, next to the
+/// human-readable message.
+/// - `NonPositivePrice` the price (or stop price) is not positive.
+/// - `NonPositiveQuantity` the quantity is not positive.
+/// - `PriceOffTick` the price is not a multiple of the tick size in strict mode,
+///   or not a multiple of the book's tick multiple.
+/// - `BelowMinSize` the quantity is below the book's minimum order size.
+/// - `AboveMaxSize` the quantity is above the book's maximum order size.
+/// - `OffLotSize` the quantity is not a multiple of the book's lot size.
+/// - `FractionalQuantity` the quantity is not a whole number in an integer book.
+/// - `InvalidDisplayQuantity` an iceberg's display quantity is out of range.
+/// - `DuplicateOrderId` an order with the same ID is still tracked by the book.
+/// - `NotAllowedInAuction` only GTC limit orders are accepted during an auction.
+/// - `PostOnlyWouldMatch` a post-only order would take liquidity.
+/// - `PegUnavailable` a pegged order's reference price does not exist.
+/// - `OutsidePriceBand` the price is too far from the last trade.
+/// - `QuantityIncrease` a quantity increase was refused by `reject_increase`.
+/// - `NotResting` the order to change is not resting on the book.
+/// - `ReduceOnlyWouldIncrease` a reduce-only order has no position to reduce.
+/// - `InvalidMinFillQuantity` a minimum fill quantity is out of range or set
+///   on a `FOK` order.
+/// - `ReductionTooLarge` a reduction exceeds the order's remaining quantity.
+/// - `EmptyOrderId` an explicit order ID is empty.
+/// - `NegativeSlippage` a market order's slippage collar is negative.
+/// - `CrossedBook` the add would leave the book locked or crossed.
+/// - `FilteredOut` the book's order filter refused the order.
+/// - `NotRestable` a seeded order is not an open `GTC` limit order.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRejectReason {
+    NonPositivePrice,
+    NonPositiveQuantity,
+    PriceOffTick,
+    BelowMinSize,
+    AboveMaxSize,
+    OffLotSize,
+    FractionalQuantity,
+    InvalidDisplayQuantity,
+    DuplicateOrderId,
+    NotAllowedInAuction,
+    PostOnlyWouldMatch,
+    PegUnavailable,
+    OutsidePriceBand,
+    QuantityIncrease,
+    NotResting,
+    ReduceOnlyWouldIncrease,
+    InvalidMinFillQuantity,
+    ReductionTooLarge,
+    EmptyOrderId,
+    NegativeSlippage,
+    CrossedBook,
+    FilteredOut,
+    NotRestable,
+}
+
+impl OrderRejectReason {
+    /// Returns an `OrderRejectedError` carrying this reason and `message`.
+    fn error(self, message: impl Into<String>) -> PyErr {
+        self.error_as::<OrderRejectedError>(message)
+    }
+
+    /// Returns an error of the `OrderRejectedError` subclass `T` carrying this
+    /// reason and `message`.
+    fn error_as<T: pyo3::PyTypeInfo>(self, message: impl Into<String>) -> PyErr {
+        let err = PyErr::new::<T, _>(message.into());
+        Python::with_gil(|py| {
+            // Setting an attribute on a fresh exception instance cannot fail
+            let _ = err.value(py).setattr("reason", self);
+        });
+        err
+    }
+}
+
+/// Represents which price a match between two orders trades at.
+/// - `Seller` trades at the sell order's price, whichever side rests. This is
+///   the default, and how the book has always priced fills.
+/// - `Maker` trades at the resting order's price.
+/// - `Taker` trades at the incoming order's price.
+/// - `Midpoint` trades halfway between the two prices, rounded to the nearest
+///   tick with halves rounded away from zero.
+///
+/// Market orders carry no price, so they always trade at the resting price.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillPriceRule {
+    Seller,
+    Maker,
+    Taker,
+    Midpoint,
+}
+
+impl FillPriceRule {
+    /// Returns the price in ticks at which `resting` and `incoming` trade.
+    fn fill_ticks(&self, resting: &Order, incoming: &Order) -> i64 {
+        if incoming.kind == OrderKind::Market {
+            return resting.price_in_ticks;
+        }
+        match self {
+            FillPriceRule::Seller => match resting.side {
+                OrderType::Sell => resting.price_in_ticks,
+                OrderType::Buy => incoming.price_in_ticks,
+            },
+            FillPriceRule::Maker => resting.price_in_ticks,
+            FillPriceRule::Taker => incoming.price_in_ticks,
+            FillPriceRule::Midpoint => {
+                ((resting.price_in_ticks + incoming.price_in_ticks) as f64 / 2.0).round() as i64
+            }
+        }
+    }
+}
+
+/// Represents how an add that would leave the book locked or crossed is
+/// handled. This can only happen when an order's matching price keeps it from
+/// trading with an order it overlaps on display price.
+/// - `Resolve` re-runs matching for the order that crossed the touch and
+///   cancels whatever still cannot trade.
+/// - `Reject` rejects the add with `CrossedBook`, leaving the book unchanged.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrossedBookPolicy {
+    Resolve,
+    Reject,
+}
+
+/// Represents the outcome of `OrderBook.cancel_detailed`.
+/// - `Canceled` the order was removed from the book.
+/// - `NotFound` no open order with the ID is tracked.
+/// - `FoundButNotInQueue` the order is tracked as open but is neither queued
+///   at its price level nor a pending stop. The book is inconsistent, so it is
+///   left unchanged.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CancelOutcome {
+    Canceled,
+    NotFound,
+    FoundButNotInQueue,
+}
+
+impl SelfTradePrevention {
+    /// Applies the policy to a resting and incoming order from the same owner.
+    fn apply(&self, resting: &mut Order, incoming: &mut Order) {
+        match self {
+            SelfTradePrevention::CancelNewest => incoming.status = OrderStatus::Canceled,
+            SelfTradePrevention::CancelOldest => resting.status = OrderStatus::Canceled,
+            SelfTradePrevention::CancelBoth => {
+                resting.status = OrderStatus::Canceled;
+                incoming.status = OrderStatus::Canceled;
+            }
+            SelfTradePrevention::DecrementBoth => {
+                let decrement = resting.quantity.min(incoming.quantity);
+                for order in [resting, incoming] {
+                    order.reduce_by(decrement);
+                    if order.quantity <= 0.0 && order.hidden_quantity <= 0.0 {
+                        order.status = OrderStatus::Canceled;
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Represents a match (fill) between two orders.
 /// Tracks details such as the quantity, price, and the involved order IDs.
 #[pyclass]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fill {
     quantity: f64,
-    price: f64,      // Fill price as a float for reporting
-    buy_id: String,  // ID of the buy order
-    sell_id: String, // ID of the sell order
-    timestamp: u64,  // Nanoseconds since the Unix epoch
+    price: f64,                        // Fill price as a float for reporting
+    buy_id: String,                    // ID of the buy order
+    sell_id: String,                   // ID of the sell order
+    timestamp: u64,                    // Nanoseconds since the Unix epoch
+    aggressor_side: Option<OrderType>, // Side of the incoming (taker) order, if known
+    maker_fee: f64,                    // Fee charged to the resting order; negative for a rebate
+    taker_fee: f64,                    // Fee charged to the incoming order
+    price_improvement: f64,            // How much better than its limit the taker traded
+    sequence: u64,                     // Execution order assigned by the book; 0 if unassigned
 }
 
 #[pymethods]
 impl Fill {
     /// Creates a new Fill record.
     #[new]
-    pub fn new(quantity: f64, price: f64, buy_id: String, sell_id: String, timestamp: u64) -> Self {
+    #[pyo3(signature = (
+        quantity,
+        price,
+        buy_id,
+        sell_id,
+        timestamp,
+        aggressor_side=None,
+        maker_fee=0.0,
+        taker_fee=0.0,
+        price_improvement=0.0,
+        sequence=0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        quantity: f64,
+        price: f64,
+        buy_id: String,
+        sell_id: String,
+        timestamp: u64,
+        aggressor_side: Option<OrderType>,
+        maker_fee: f64,
+        taker_fee: f64,
+        price_improvement: f64,
+        sequence: u64,
+    ) -> Self {
         Self {
             quantity,
             price,
             buy_id,
             sell_id,
             timestamp,
+            aggressor_side,
+            maker_fee,
+            taker_fee,
+            price_improvement,
+            sequence,
         }
     }
 
     /// Returns a string representation of the Fill.
     fn __repr__(&self) -> String {
+        let aggressor = match &self.aggressor_side {
+            Some(side) => format!("{:?}", side),
+            None => "Unknown".to_string(),
+        };
         format!(
-            "[{:.2} @ {:.2}] Buy: {}, Sell: {}, Filled at {}",
-            self.quantity, self.price, self.buy_id, self.sell_id, self.timestamp
+            "[#{} {:.2} @ {:.2}] Buy: {}, Sell: {}, Filled at {}, Aggressor: {}",
+            self.sequence,
+            self.quantity,
+            self.price,
+            self.buy_id,
+            self.sell_id,
+            self.timestamp,
+            aggressor
         )
     }
 
@@ -85,10 +592,48 @@ impl Fill {
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+
+    /// Getter for the side of the incoming (taker) order. None for a fill
+    /// built by hand without one.
+    #[getter]
+    pub fn aggressor_side(&self) -> Option<OrderType> {
+        self.aggressor_side.clone()
+    }
+
+    /// Getter for the fee charged to the resting (maker) order.
+    #[getter]
+    pub fn maker_fee(&self) -> f64 {
+        self.maker_fee
+    }
+
+    /// Getter for the fee charged to the incoming (taker) order.
+    #[getter]
+    pub fn taker_fee(&self) -> f64 {
+        self.taker_fee
+    }
+
+    /// Getter for the price improvement: how much better than its limit price
+    /// the incoming (taker) order traded. This is never negative, and is 0 for
+    /// fills at the taker's limit and for market orders, which have no limit.
+    #[getter]
+    pub fn price_improvement(&self) -> f64 {
+        self.price_improvement
+    }
+
+    /// Getter for the sequence number: the position of the fill among every
+    /// fill its book has produced, starting at 1. Unlike timestamps, sequence
+    /// numbers never tie, so they give a total order of executions. Fills not
+    /// produced by a book have sequence 0.
+    #[getter]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
 }
 
 /// Represents a single order in the order book.
 /// Contains details such as price, quantity, side (Buy/Sell), and status.
+/// Orders compare equal and hash by ID alone, not by their fields, since an
+/// ID identifies one order across all of its copies.
 #[pyclass]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
@@ -98,6 +643,23 @@ pub struct Order {
     quantity: f64,
     status: OrderStatus,
     timestamp: u64, // Nanoseconds since the Unix epoch
+    time_in_force: TimeInForce,
+    post_only: bool,               // Reject rather than take liquidity
+    display_quantity: Option<f64>, // Visible slice size for iceberg orders
+    hidden_quantity: f64,          // Iceberg reserve not yet displayed
+    kind: OrderKind,
+    stop_price_in_ticks: Option<i64>, // Trigger price for stop orders
+    expires_at: Option<u64>,          // Good-till-date expiry in nanoseconds
+    owner: Option<String>,            // Account used for self-trade prevention
+    peg_reference: Option<PegReference>, // Price tracked by pegged orders
+    peg_offset_ticks: i64,            // Distance from the peg reference
+    reduce_only: bool,                // Only ever reduce the owner's position
+    min_fill_quantity: Option<f64>,   // Smallest execution the order will take
+    matching_price_in_ticks: Option<i64>, // Price used for matching, if not the display price
+    stp_group: Option<String>,        // Self-trade prevention scope shared across owners
+    filled_quantity: f64,             // Quantity executed across all fills so far
+    original_quantity: f64,           // Quantity at construction, never changed
+    max_slippage_ticks: Option<i64>,  // How far past the touch a market order may sweep
 }
 
 #[pymethods]
@@ -108,39 +670,124 @@ impl Order {
     /// - `side`: The side of the order (`Buy` or `Sell`).
     /// - `price_in_ticks`: The price in integer ticks (scaled by tick size).
     /// - `quantity`: The quantity of the order.
+    /// - `time_in_force`: How long the order remains active (defaults to `GTC`).
+    /// - `post_only`: If `true`, the order is rejected instead of taking liquidity.
+    /// - `expires_at`: Optional expiry time in nanoseconds (good-till-date).
+    /// - `timestamp`: Optional creation time in nanoseconds (defaults to now).
+    /// - `owner`: Optional account identifier used for self-trade prevention.
+    /// - `matching_price_in_ticks`: Optional price in ticks used for matching
+    ///   instead of `price_in_ticks` (defaults to `price_in_ticks`).
+    /// - `stp_group`: Optional self-trade prevention group, used instead of
+    ///   `owner` when set.
     ///
     /// # Errors
-    /// - Returns an error if `price_in_ticks` or `quantity` is non-positive.
+    /// - Returns an error if `price_in_ticks`, `matching_price_in_ticks` or
+    ///   `quantity` is non-positive.
     #[new]
-    pub fn new(side: OrderType, price_in_ticks: i64, quantity: f64) -> PyResult<Self> {
+    #[pyo3(signature = (
+        side,
+        price_in_ticks,
+        quantity,
+        time_in_force=TimeInForce::GTC,
+        post_only=false,
+        expires_at=None,
+        timestamp=None,
+        owner=None,
+        matching_price_in_ticks=None,
+        stp_group=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        side: OrderType,
+        price_in_ticks: i64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        expires_at: Option<u64>,
+        timestamp: Option<u64>,
+        owner: Option<String>,
+        matching_price_in_ticks: Option<i64>,
+        stp_group: Option<String>,
+    ) -> PyResult<Self> {
         if price_in_ticks <= 0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "price_in_ticks must be positive",
-            ));
+            return Err(
+                OrderRejectReason::NonPositivePrice.error("price_in_ticks must be positive")
+            );
         }
-        if quantity <= 0.0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "quantity must be positive",
-            ));
+        if matching_price_in_ticks.is_some_and(|ticks| ticks <= 0) {
+            return Err(OrderRejectReason::NonPositivePrice
+                .error("matching_price_in_ticks must be positive"));
         }
+        Self::build(
+            side,
+            price_in_ticks,
+            quantity,
+            time_in_force,
+            post_only,
+            expires_at,
+            timestamp,
+            owner,
+            matching_price_in_ticks,
+            stp_group,
+        )
+    }
 
-        let id = Uuid::new_v4().to_string();
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_nanos() as u64;
-
-        Ok(Self {
-            id,
+    /// Creates a new order like `Order(...)`, but with the given `id` instead
+    /// of a freshly generated UUID. Use it to rebuild orders whose IDs were
+    /// assigned by an external system or recorded in a log.
+    ///
+    /// # Errors
+    /// - Returns an error if `id` is empty.
+    /// - Returns the same errors as `Order(...)`.
+    #[staticmethod]
+    #[pyo3(signature = (
+        id,
+        side,
+        price_in_ticks,
+        quantity,
+        time_in_force=TimeInForce::GTC,
+        post_only=false,
+        expires_at=None,
+        timestamp=None,
+        owner=None,
+        matching_price_in_ticks=None,
+        stp_group=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_id(
+        id: String,
+        side: OrderType,
+        price_in_ticks: i64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        expires_at: Option<u64>,
+        timestamp: Option<u64>,
+        owner: Option<String>,
+        matching_price_in_ticks: Option<i64>,
+        stp_group: Option<String>,
+    ) -> PyResult<Self> {
+        if id.is_empty() {
+            return Err(OrderRejectReason::EmptyOrderId.error("id must not be empty"));
+        }
+        let mut order = Self::new(
             side,
             price_in_ticks,
             quantity,
-            status: OrderStatus::Open,
-            timestamp: now,
-        })
+            time_in_force,
+            post_only,
+            expires_at,
+            timestamp,
+            owner,
+            matching_price_in_ticks,
+            stp_group,
+        )?;
+        order.id = id;
+        Ok(order)
     }
 
-    /// Determines whether this order can match with another order.
+    /// Determines whether this order can match with another order. Prices are
+    /// compared using each order's matching price.
     ///
     /// # Arguments
     /// - `other`: The other order to match against.
@@ -152,48 +799,33 @@ impl Order {
         if self.side == other.side {
             return false;
         }
+        if self.kind == OrderKind::Market || other.kind == OrderKind::Market {
+            return true;
+        }
         match self.side {
-            OrderType::Buy => self.price_in_ticks >= other.price_in_ticks,
-            OrderType::Sell => self.price_in_ticks <= other.price_in_ticks,
+            OrderType::Buy => self.matching_ticks() >= other.matching_ticks(),
+            OrderType::Sell => self.matching_ticks() <= other.matching_ticks(),
         }
     }
 
     /// Attempts to fill this order with another incoming order.
-    /// Updates the quantities and statuses of both orders.
-    fn fill(&mut self, incoming: &mut Order, tick_size: f64) -> Option<Fill> {
-        if !self.can_match(incoming) {
-            return None;
-        }
-
-        let fill_quantity = self.quantity.min(incoming.quantity);
-        self.quantity -= fill_quantity;
-        incoming.quantity -= fill_quantity;
-
-        if self.quantity <= 0.0 {
-            self.status = OrderStatus::Filled;
-        }
-        if incoming.quantity <= 0.0 {
-            incoming.status = OrderStatus::Filled;
-        }
-
-        let final_ticks = if self.side == OrderType::Sell {
-            self.price_in_ticks
-        } else {
-            incoming.price_in_ticks
-        };
-
-        let fill_price = (final_ticks as f64) * tick_size;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_nanos() as u64;
-
-        Some(Fill::new(
-            fill_quantity,
-            fill_price,
-            self.id.clone(),
-            incoming.id.clone(),
-            now,
+    /// Updates the quantities and statuses of both orders. The orders match on
+    /// their matching prices, but the fill trades at the sell order's display
+    /// price and is stamped with `timestamp` if given, otherwise with the
+    /// current wall-clock time.
+    #[pyo3(signature = (incoming, tick_size, timestamp=None))]
+    fn fill(
+        &mut self,
+        incoming: &mut Order,
+        tick_size: f64,
+        timestamp: Option<u64>,
+    ) -> PyResult<Option<Fill>> {
+        Ok(self.fill_up_to(
+            incoming,
+            f64::INFINITY,
+            &FillPriceRule::Seller,
+            TickScale::new(tick_size)?,
+            timestamp,
         ))
     }
 
@@ -202,6 +834,19 @@ impl Order {
         self.status == OrderStatus::Open
     }
 
+    /// Checks whether `other` is the same order, by ID. Two copies of an
+    /// order compare equal even if one has since been filled or modified.
+    fn __eq__(&self, other: PyRef<'_, Order>) -> bool {
+        *self == *other
+    }
+
+    /// Returns a hash of the order ID, consistent with `__eq__`.
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// Returns a string representation of the order.
     fn __repr__(&self) -> String {
         format!(
@@ -245,321 +890,4155 @@ impl Order {
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
-}
 
-/// Represents the main order book for matching buy and sell orders.
-#[pyclass]
-pub struct OrderBook {
-    buy_orders: BTreeMap<i64, VecDeque<Order>>, // Buy-side orders, keyed by price
-    sell_orders: BTreeMap<i64, VecDeque<Order>>, // Sell-side orders, keyed by price
-    orders: HashMap<String, Order>,             // Map of UUID -> Order for quick lookup
-    tick_size: f64,                             // Tick size for price scaling
-}
+    /// Getter for the time in force.
+    #[getter]
+    pub fn time_in_force(&self) -> TimeInForce {
+        self.time_in_force.clone()
+    }
 
-#[pymethods]
-impl OrderBook {
-    /// Creates a new OrderBook with a specified tick size.
-    #[new]
-    #[pyo3(signature = (tick_size=0.01))]
-    pub fn new(tick_size: f64) -> Self {
-        Self {
-            buy_orders: BTreeMap::new(),
-            sell_orders: BTreeMap::new(),
-            orders: HashMap::new(),
-            tick_size,
-        }
+    /// Getter for the iceberg display quantity, if any.
+    #[getter]
+    pub fn display_quantity(&self) -> Option<f64> {
+        self.display_quantity
     }
 
-    /// Creates an order (but does not add to the book) based off the book's tick size.
-    #[pyo3(text_signature = "(self, side, price, quantity)")]
-    pub fn create_order(&self, side: OrderType, price: f64, quantity: f64) -> PyResult<Order> {
-        if price <= 0.0 || quantity <= 0.0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Price and quantity must be positive",
-            ));
-        }
+    /// Getter for the hidden iceberg reserve.
+    #[getter]
+    pub fn hidden_quantity(&self) -> f64 {
+        self.hidden_quantity
+    }
 
-        let price_in_ticks = (price / self.tick_size).round() as i64;
-        if price_in_ticks <= 0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Resulting price_in_ticks must be positive",
-            ));
-        }
+    /// Getter for the order kind.
+    #[getter]
+    pub fn kind(&self) -> OrderKind {
+        self.kind.clone()
+    }
 
-        Order::new(side, price_in_ticks, quantity)
+    /// Getter for the stop trigger price in ticks, if any.
+    #[getter]
+    pub fn stop_price_in_ticks(&self) -> Option<i64> {
+        self.stop_price_in_ticks
     }
 
-    /// Adds an order to the book, attempting to match it with resting orders.
-    #[pyo3(text_signature = "(self, order)")]
-    pub fn add(&mut self, mut incoming_order: Order) -> PyResult<Vec<Fill>> {
-        let mut fills = Vec::new();
+    /// Getter for the expiry time, if any.
+    #[getter]
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
 
-        match incoming_order.side {
-            OrderType::Buy => {
-                while incoming_order.is_open() {
-                    let (_best_sell_price, resting_sell) = {
-                        // Restrict the mutable borrow of `sell_queue` to this block
-                        let (best_sell_price, sell_queue) = match self.sell_orders.iter_mut().next()
-                        {
-                            Some((k, q)) => (*k, q),
-                            None => break,
-                        };
+    /// Getter for the owner, if any.
+    #[getter]
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
 
-                        if incoming_order.price_in_ticks < best_sell_price {
-                            break;
-                        }
+    /// Getter for the self-trade prevention group, if any.
+    #[getter]
+    pub fn stp_group(&self) -> Option<&str> {
+        self.stp_group.as_deref()
+    }
 
-                        let mut resting_sell = sell_queue
-                            .pop_front()
-                            .expect("Queue is not empty if it exists in map");
+    /// Getter for the quantity the order was created with. Unlike `quantity`,
+    /// it is never changed by fills, modifications or reductions.
+    #[getter]
+    pub fn original_quantity(&self) -> f64 {
+        self.original_quantity
+    }
 
-                        // Process the fill
-                        if let Some(fill) = resting_sell.fill(&mut incoming_order, self.tick_size) {
-                            fills.push(fill);
-                        }
+    /// Getter for a market order's slippage collar in ticks, if any.
+    #[getter]
+    pub fn max_slippage_ticks(&self) -> Option<i64> {
+        self.max_slippage_ticks
+    }
 
-                        // Push back the partially filled resting order, if necessary
-                        if resting_sell.is_open() {
-                            sell_queue.push_front(resting_sell.clone());
-                        }
+    /// Getter for the quantity executed across all of the order's fills. A
+    /// `Filled` order has nothing remaining, so this is its whole size:
+    /// `original_quantity` as amended by any `modify_quantity` or `reduce`
+    /// calls made while it rested.
+    #[getter]
+    pub fn filled_quantity(&self) -> f64 {
+        self.filled_quantity
+    }
 
-                        if sell_queue.is_empty() {
-                            self.sell_orders.remove(&best_sell_price);
-                        }
+    /// Getter for the quantity still left to fill, including any hidden
+    /// iceberg reserve.
+    #[getter]
+    pub fn remaining_quantity(&self) -> f64 {
+        self.quantity + self.hidden_quantity
+    }
 
-                        (best_sell_price, resting_sell)
-                    };
+    /// Getter for the fraction of the order that has filled: the filled
+    /// quantity over the filled plus remaining quantity. This is 0 for an
+    /// unfilled order, between 0 and 1 for a partially filled one and 1 for a
+    /// `Filled` order.
+    #[getter]
+    pub fn fill_ratio(&self) -> f64 {
+        let total = self.filled_quantity + self.remaining_quantity();
+        if total > 0.0 {
+            self.filled_quantity / total
+        } else {
+            0.0
+        }
+    }
 
-                    // Update the resting sell order and incoming order in the `orders` map
-                    self.update_order(&resting_sell);
-                    self.update_order(&incoming_order);
-                }
+    /// Getter for the post-only flag.
+    #[getter]
+    pub fn post_only(&self) -> bool {
+        self.post_only
+    }
 
-                if incoming_order.is_open() {
-                    let price_ticks = incoming_order.price_in_ticks;
-                    self.buy_orders
-                        .entry(price_ticks)
-                        .or_default()
-                        .push_back(incoming_order.clone());
-                }
-            }
+    /// Getter for the peg reference, if this is a pegged order.
+    #[getter]
+    pub fn peg_reference(&self) -> Option<PegReference> {
+        self.peg_reference.clone()
+    }
 
-            OrderType::Sell => {
-                while incoming_order.is_open() {
-                    let (_best_buy_price, resting_buy) = {
-                        // Restrict the mutable borrow of `buy_queue` to this block
-                        let (best_buy_price, buy_queue) =
-                            match self.buy_orders.iter_mut().next_back() {
-                                Some((k, q)) => (*k, q),
-                                None => break,
-                            };
-
-                        if incoming_order.price_in_ticks > best_buy_price {
-                            break;
-                        }
+    /// Getter for the peg offset in ticks.
+    #[getter]
+    pub fn peg_offset_ticks(&self) -> i64 {
+        self.peg_offset_ticks
+    }
 
-                        let mut resting_buy = buy_queue
-                            .pop_front()
-                            .expect("Queue is not empty if it exists in map");
+    /// Getter for whether the order may only reduce its owner's position.
+    #[getter]
+    pub fn reduce_only(&self) -> bool {
+        self.reduce_only
+    }
 
-                        // Process the fill
-                        if let Some(fill) = resting_buy.fill(&mut incoming_order, self.tick_size) {
-                            fills.push(fill);
-                        }
+    /// Getter for the smallest execution the order will take, if any.
+    #[getter]
+    pub fn min_fill_quantity(&self) -> Option<f64> {
+        self.min_fill_quantity
+    }
 
-                        // Push back the partially filled resting order, if necessary
-                        if resting_buy.is_open() {
-                            buy_queue.push_front(resting_buy.clone());
-                        }
+    /// Getter for the price in ticks used for matching. This is the display
+    /// price, `price_in_ticks`, unless the order was given a separate one.
+    #[getter]
+    pub fn matching_price_in_ticks(&self) -> i64 {
+        self.matching_ticks()
+    }
+}
+
+impl PartialEq for Order {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Order {}
+
+impl Hash for Order {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl Order {
+    /// Creates an order like `Order::new` without requiring positive prices,
+    /// for books that allow zero and negative prices and for market and pegged
+    /// orders, which are priced later. Callers check prices against their own
+    /// rules first.
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        side: OrderType,
+        price_in_ticks: i64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        expires_at: Option<u64>,
+        timestamp: Option<u64>,
+        owner: Option<String>,
+        matching_price_in_ticks: Option<i64>,
+        stp_group: Option<String>,
+    ) -> PyResult<Self> {
+        if quantity <= 0.0 {
+            return Err(OrderRejectReason::NonPositiveQuantity.error("quantity must be positive"));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = timestamp.unwrap_or_else(now_nanos);
+
+        Ok(Self {
+            id,
+            side,
+            price_in_ticks,
+            quantity,
+            status: OrderStatus::Open,
+            timestamp: now,
+            time_in_force,
+            post_only,
+            display_quantity: None,
+            hidden_quantity: 0.0,
+            kind: OrderKind::Limit,
+            stop_price_in_ticks: None,
+            expires_at,
+            owner,
+            peg_reference: None,
+            peg_offset_ticks: 0,
+            reduce_only: false,
+            min_fill_quantity: None,
+            matching_price_in_ticks,
+            stp_group,
+            filled_quantity: 0.0,
+            original_quantity: quantity,
+            max_slippage_ticks: None,
+        })
+    }
+
+    /// Checks whether every field of this order equals the corresponding field
+    /// of `other`, unlike `==`, which only compares IDs.
+    fn same_state(&self, other: &Order) -> bool {
+        // Destructured so that a new field cannot be left out of the comparison
+        let Order {
+            id,
+            side,
+            price_in_ticks,
+            quantity,
+            status,
+            timestamp,
+            time_in_force,
+            post_only,
+            display_quantity,
+            hidden_quantity,
+            kind,
+            stop_price_in_ticks,
+            expires_at,
+            owner,
+            peg_reference,
+            peg_offset_ticks,
+            reduce_only,
+            min_fill_quantity,
+            matching_price_in_ticks,
+            stp_group,
+            filled_quantity,
+            original_quantity,
+            max_slippage_ticks,
+        } = self;
+        *id == other.id
+            && *side == other.side
+            && *price_in_ticks == other.price_in_ticks
+            && *quantity == other.quantity
+            && *status == other.status
+            && *timestamp == other.timestamp
+            && *time_in_force == other.time_in_force
+            && *post_only == other.post_only
+            && *display_quantity == other.display_quantity
+            && *hidden_quantity == other.hidden_quantity
+            && *kind == other.kind
+            && *stop_price_in_ticks == other.stop_price_in_ticks
+            && *expires_at == other.expires_at
+            && *owner == other.owner
+            && *peg_reference == other.peg_reference
+            && *peg_offset_ticks == other.peg_offset_ticks
+            && *reduce_only == other.reduce_only
+            && *min_fill_quantity == other.min_fill_quantity
+            && *matching_price_in_ticks == other.matching_price_in_ticks
+            && *stp_group == other.stp_group
+            && *filled_quantity == other.filled_quantity
+            && *original_quantity == other.original_quantity
+            && *max_slippage_ticks == other.max_slippage_ticks
+    }
+
+    /// Returns the price in ticks this order matches at.
+    fn matching_ticks(&self) -> i64 {
+        self.matching_price_in_ticks.unwrap_or(self.price_in_ticks)
+    }
+
+    /// Returns the smallest execution this order will take as the incoming
+    /// order: its `min_fill_quantity`, capped at its remaining quantity so
+    /// that a final, smaller remainder can still complete.
+    fn min_fill_threshold(&self) -> Option<f64> {
+        self.min_fill_quantity
+            .map(|minimum| minimum.min(self.quantity + self.hidden_quantity))
+    }
+
+    /// Checks whether this order is willing to trade at `price_in_ticks`.
+    fn accepts_price(&self, price_in_ticks: i64) -> bool {
+        match (&self.kind, &self.side) {
+            (OrderKind::Market, _) => true,
+            (OrderKind::Limit, OrderType::Buy) => self.matching_ticks() >= price_in_ticks,
+            (OrderKind::Limit, OrderType::Sell) => self.matching_ticks() <= price_in_ticks,
+        }
+    }
+
+    /// Checks whether a stop order has been triggered by the last trade price.
+    /// Buy stops trigger at or above their stop price, sell stops at or below.
+    /// Orders without a stop price are always considered triggered.
+    fn stop_triggered(&self, last_trade_ticks: Option<i64>) -> bool {
+        match (self.stop_price_in_ticks, last_trade_ticks) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(stop), Some(last)) => match self.side {
+                OrderType::Buy => last >= stop,
+                OrderType::Sell => last <= stop,
+            },
+        }
+    }
+
+    /// Checks whether matching against `other` would be a self-trade. An
+    /// `stp_group` takes priority over `owner`: orders in a group only
+    /// self-trade with orders in the same group, and orders without a group
+    /// only with other ungrouped orders from the same owner.
+    fn self_trades_with(&self, other: &Order) -> bool {
+        match (&self.stp_group, &other.stp_group) {
+            (Some(group), Some(other_group)) => group == other_group,
+            (None, None) => self.owner.is_some() && self.owner == other.owner,
+            _ => false,
+        }
+    }
+
+    /// Checks whether a good-till-date order has expired as of `now`.
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Reduces the visible quantity by `quantity`, snapping any float dust
+    /// left behind to exactly zero.
+    fn reduce_by(&mut self, quantity: f64) {
+        self.quantity -= quantity;
+        if self.quantity.abs() < QUANTITY_EPSILON {
+            self.quantity = 0.0;
+        }
+    }
+
+    /// Refreshes an exhausted iceberg slice from its hidden reserve.
+    /// Returns `true` if the visible quantity was replenished.
+    fn replenish(&mut self) -> bool {
+        match self.display_quantity {
+            Some(display) if self.quantity <= 0.0 && self.hidden_quantity > 0.0 => {
+                let slice = display.min(self.hidden_quantity);
+                self.quantity = slice;
+                self.hidden_quantity -= slice;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Fills this resting order against `incoming` like `fill`, trading at
+    /// most `max_quantity` at the price chosen by `price_rule`.
+    fn fill_up_to(
+        &mut self,
+        incoming: &mut Order,
+        max_quantity: f64,
+        price_rule: &FillPriceRule,
+        tick: TickScale,
+        timestamp: Option<u64>,
+    ) -> Option<Fill> {
+        if !self.can_match(incoming) {
+            return None;
+        }
+
+        let fill_quantity = self.quantity.min(incoming.quantity).min(max_quantity);
+        self.reduce_by(fill_quantity);
+        incoming.reduce_by(fill_quantity);
+        self.filled_quantity += fill_quantity;
+        incoming.filled_quantity += fill_quantity;
+
+        if self.quantity <= 0.0 && self.hidden_quantity <= 0.0 {
+            self.status = OrderStatus::Filled;
+        }
+        if incoming.quantity <= 0.0 && incoming.hidden_quantity <= 0.0 {
+            incoming.status = OrderStatus::Filled;
+        }
+
+        let final_ticks = price_rule.fill_ticks(self, incoming);
+        let fill_price = tick.to_price(final_ticks);
+        let improvement_ticks = match (&incoming.kind, &incoming.side) {
+            (OrderKind::Market, _) => 0,
+            (OrderKind::Limit, OrderType::Buy) => incoming.price_in_ticks - final_ticks,
+            (OrderKind::Limit, OrderType::Sell) => final_ticks - incoming.price_in_ticks,
+        };
+        let now = timestamp.unwrap_or_else(now_nanos);
+
+        // `self` is the resting (maker) order and `incoming` the taker
+        let (buy_id, sell_id) = match self.side {
+            OrderType::Buy => (self.id.clone(), incoming.id.clone()),
+            OrderType::Sell => (incoming.id.clone(), self.id.clone()),
+        };
+
+        Some(Fill::new(
+            fill_quantity,
+            fill_price,
+            buy_id,
+            sell_id,
+            now,
+            Some(incoming.side.clone()),
+            0.0,
+            0.0,
+            tick.to_price(improvement_ticks.max(0)),
+            0,
+        ))
+    }
+
+    /// Matches this resting order against `incoming`, applying `stp_policy`
+    /// instead of filling when both orders share an owner.
+    fn match_against(
+        &mut self,
+        incoming: &mut Order,
+        stp_policy: &SelfTradePrevention,
+        price_rule: &FillPriceRule,
+        tick: TickScale,
+        timestamp: Option<u64>,
+    ) -> Option<Fill> {
+        if incoming.self_trades_with(self) {
+            stp_policy.apply(self, incoming);
+            None
+        } else {
+            self.fill_up_to(incoming, f64::INFINITY, price_rule, tick, timestamp)
+        }
+    }
+}
+
+/// Represents a mutating operation applied to an `OrderBook`, recorded so the
+/// book can be rebuilt with `OrderBook.replay`.
+/// - `Add` submits `order`, stamping its fills with `timestamp`.
+/// - `Cancel` cancels the order with `order_id`.
+/// - `Modify` changes the quantity of the resting order with `order_id`.
+/// - `Reduce` pulls `amount` from the resting order with `order_id`.
+/// - `RepricePegs` reprices every pegged order, stamping its fills with `timestamp`.
+/// - `StartAuction` starts collecting orders without matching.
+/// - `Uncross` ends an auction, stamping its fills with `timestamp`.
+// `Add` holds the order inline so Python can read it back as a plain `Order`
+#[allow(clippy::large_enum_variant)]
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Event {
+    Add { order: Order, timestamp: u64 },
+    Cancel { order_id: String },
+    Modify { order_id: String, new_quantity: f64 },
+    Reduce { order_id: String, amount: f64 },
+    RepricePegs { timestamp: u64 },
+    StartAuction {},
+    Uncross { timestamp: u64 },
+}
+
+/// A serializable snapshot of an `OrderBook`'s persistent state. Orders are
+/// keyed in a `BTreeMap` so the same book always serializes identically.
+#[derive(Serialize, Deserialize)]
+struct OrderBookSnapshot {
+    tick_size: f64,
+    stp_policy: SelfTradePrevention,
+    matching_mode: MatchingMode,
+    fill_price_rule: FillPriceRule,
+    min_quantity: Option<f64>,
+    max_quantity: Option<f64>,
+    lot_size: Option<f64>,
+    price_band_ticks: Option<i64>,
+    level_priority: LevelPriority,
+    quantity_is_integer: bool,
+    maker_fee_bps: f64,
+    taker_fee_bps: f64,
+    max_orders: Option<usize>,
+    crossed_book_policy: CrossedBookPolicy,
+    tick_multiple: i64,
+    allow_negative_prices: bool,
+    buy_orders: BTreeMap<i64, VecDeque<Order>>,
+    sell_orders: BTreeMap<i64, VecDeque<Order>>,
+    orders: BTreeMap<String, Order>,
+    stop_orders: Vec<Order>,
+    last_trade: Option<Fill>,
+    fills: Vec<Fill>,
+    in_auction: bool,
+    positions: BTreeMap<String, f64>,
+    seq: u64,
+}
+
+/// Running counters of book activity, reported by `OrderBook.stats`.
+#[derive(Debug, Clone, Default)]
+struct BookStats {
+    orders_added: u64,     // Orders accepted by `add` and its variants
+    orders_canceled: u64,  // Orders removed by a cancel or expiry
+    fills: u64,            // Fills produced
+    volume: f64,           // Quantity traded across those fills
+    times_emptied: u64,    // Times an add or cancel left no resting orders
+    crosses_resolved: u64, // Adds that left the book locked or crossed until resolved
+}
+
+/// Represents the main order book for matching buy and sell orders.
+#[pyclass]
+pub struct OrderBook {
+    buy_orders: BTreeMap<i64, VecDeque<Order>>, // Buy-side orders, keyed by price
+    sell_orders: BTreeMap<i64, VecDeque<Order>>, // Sell-side orders, keyed by price
+    orders: HashMap<String, Order>,             // Map of UUID -> Order for quick lookup
+    tick: TickScale,                            // Tick size for price scaling
+    stop_orders: Vec<Order>,                    // Untriggered stops, in submission order
+    last_trade: Option<Fill>,                   // The most recent fill
+    stp_policy: SelfTradePrevention,            // How self-trades are prevented
+    self_trade_canceled: Vec<Order>,            // Orders canceled by STP in the last add
+    evicted: Vec<Order>,                        // Orders evicted by `max_orders` in the last add
+    matching_mode: MatchingMode,                // How a price level is allocated
+    fill_price_rule: FillPriceRule,             // Which price a match trades at
+    min_quantity: Option<f64>,                  // Smallest accepted order quantity
+    max_quantity: Option<f64>,                  // Largest accepted order quantity
+    lot_size: Option<f64>,                      // Order quantities must be multiples of this
+    price_band_ticks: Option<i64>,              // Max distance from the last trade price
+    level_priority: LevelPriority,              // Which order at a level matches first
+    quantity_is_integer: bool,                  // Quantities must be whole numbers
+    maker_fee_bps: f64,                         // Fee charged to the resting side of a fill
+    taker_fee_bps: f64,                         // Fee charged to the incoming side of a fill
+    max_orders: Option<usize>,                  // Resting orders kept before evicting the oldest
+    crossed_book_policy: CrossedBookPolicy,     // Whether crossing adds are resolved or rejected
+    tick_multiple: i64,                         // Limit prices must be a multiple of this in ticks
+    allow_negative_prices: bool,                // Zero and negative prices are accepted
+    in_auction: bool,                           // Collect orders without matching
+    positions: HashMap<String, f64>,            // Net filled quantity per owner
+    events: Vec<Event>,                         // Append-only log of mutating operations
+    fills: Vec<Fill>,                           // Every fill produced, in execution order
+    seq: u64,                                   // Sequence number of the latest fill
+    stats: BookStats,                           // Activity counters since the last reset
+    expiries: BinaryHeap<Reverse<(u64, String)>>, // Min-heap of GTD expiries, deleted lazily
+    on_fill: Option<Py<PyAny>>,                 // Called with each fill produced by an add
+    order_filter: Option<Py<PyAny>>,            // Vets each order before an add applies it
+}
+
+#[pymethods]
+impl OrderBook {
+    /// Creates a new OrderBook with a specified tick size, self-trade
+    /// prevention policy, matching mode, fill price rule and level priority.
+    /// Order quantities can optionally be limited to `[min_quantity,
+    /// max_quantity]` and to multiples of `lot_size`, and limit prices to
+    /// within `price_band_ticks` of the last trade price; all of these are
+    /// unconstrained by default. Setting `quantity_is_integer` restricts every
+    /// quantity to whole numbers, so fills never leave fractional residue.
+    /// Each fill is charged `maker_fee_bps` and `taker_fee_bps` basis points of
+    /// its notional; a negative maker fee is a rebate. Both default to zero.
+    /// With `max_orders` set, an add that leaves more resting orders than the
+    /// cap evicts the oldest order at the least aggressive price (the lowest
+    /// bid or highest ask), keeping memory bounded in long simulations.
+    /// `crossed_book_policy` decides whether an add that would leave the book
+    /// locked or crossed is resolved by re-running matching (the default) or
+    /// rejected. A `tick_multiple` above 1 enforces a coarser price grid than
+    /// the tick, rejecting limit orders whose price in ticks is not a multiple
+    /// of it. Prices must be positive unless `allow_negative_prices` is set,
+    /// e.g. for calendar spreads, in which case orders created by the book
+    /// may have zero or negative prices and match on them as usual.
+    ///
+    /// # Errors
+    /// - Returns an error if `tick_size` is not a positive, finite number.
+    /// - Returns an error if a fee rate is not finite.
+    /// - Returns an error if `max_orders` or `tick_multiple` is not positive.
+    #[new]
+    #[pyo3(signature = (
+        tick_size=0.01,
+        stp_policy=SelfTradePrevention::CancelNewest,
+        matching_mode=MatchingMode::Fifo,
+        fill_price_rule=FillPriceRule::Seller,
+        min_quantity=None,
+        max_quantity=None,
+        lot_size=None,
+        price_band_ticks=None,
+        level_priority=LevelPriority::Time,
+        quantity_is_integer=false,
+        maker_fee_bps=0.0,
+        taker_fee_bps=0.0,
+        max_orders=None,
+        crossed_book_policy=CrossedBookPolicy::Resolve,
+        tick_multiple=1,
+        allow_negative_prices=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        tick_size: f64,
+        stp_policy: SelfTradePrevention,
+        matching_mode: MatchingMode,
+        fill_price_rule: FillPriceRule,
+        min_quantity: Option<f64>,
+        max_quantity: Option<f64>,
+        lot_size: Option<f64>,
+        price_band_ticks: Option<i64>,
+        level_priority: LevelPriority,
+        quantity_is_integer: bool,
+        maker_fee_bps: f64,
+        taker_fee_bps: f64,
+        max_orders: Option<usize>,
+        crossed_book_policy: CrossedBookPolicy,
+        tick_multiple: i64,
+        allow_negative_prices: bool,
+    ) -> PyResult<Self> {
+        let tick = TickScale::new(tick_size)?;
+        if !(maker_fee_bps.is_finite() && taker_fee_bps.is_finite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Fee rates must be finite numbers",
+            ));
+        }
+        if max_orders == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "max_orders must be positive",
+            ));
+        }
+        if tick_multiple < 1 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "tick_multiple must be positive",
+            ));
+        }
+
+        Ok(Self {
+            buy_orders: BTreeMap::new(),
+            sell_orders: BTreeMap::new(),
+            orders: HashMap::new(),
+            tick,
+            stop_orders: Vec::new(),
+            last_trade: None,
+            stp_policy,
+            self_trade_canceled: Vec::new(),
+            evicted: Vec::new(),
+            matching_mode,
+            fill_price_rule,
+            min_quantity,
+            max_quantity,
+            lot_size,
+            price_band_ticks,
+            level_priority,
+            quantity_is_integer,
+            maker_fee_bps,
+            taker_fee_bps,
+            max_orders,
+            crossed_book_policy,
+            tick_multiple,
+            allow_negative_prices,
+            in_auction: false,
+            positions: HashMap::new(),
+            events: Vec::new(),
+            on_fill: None,
+            order_filter: None,
+            fills: Vec::new(),
+            seq: 0,
+            stats: BookStats::default(),
+            expiries: BinaryHeap::new(),
+        })
+    }
+
+    /// Creates an order (but does not add to the book) based off the book's tick size.
+    /// The price is rounded onto the tick grid according to `rounding`, unless
+    /// `strict` is set, in which case a price that is not a multiple of the
+    /// tick size is rejected. A `reduce_only` order is trimmed when added so
+    /// that it can only reduce its owner's position.
+    ///
+    /// With `min_fill_quantity` set, the order only takes executions of at
+    /// least that size (or its whole remainder, if smaller) when it is added.
+    /// Matching stops at the first resting order (or, in pro-rata books, the
+    /// first price level) that cannot meet the minimum, and the unfilled
+    /// remainder is then canceled, whatever the time in force, rather than
+    /// left resting across the book. An order that simply runs out of crossing
+    /// liquidity rests as usual, and the minimum no longer applies once it
+    /// rests. `FOK` orders already fill in full and cannot set a minimum.
+    ///
+    /// A `matching_price`, rounded like `price`, makes the order match as if
+    /// it were at that price while it still rests at, and reports fills at,
+    /// its display `price`.
+    ///
+    /// An `stp_group` scopes self-trade prevention across owners: when set, it
+    /// is checked instead of `owner`, so orders from different accounts in the
+    /// same group never trade with each other.
+    #[pyo3(signature = (
+        side,
+        price,
+        quantity,
+        time_in_force=TimeInForce::GTC,
+        post_only=false,
+        expires_at=None,
+        timestamp=None,
+        owner=None,
+        strict=false,
+        rounding=RoundingMode::Nearest,
+        reduce_only=false,
+        min_fill_quantity=None,
+        matching_price=None,
+        stp_group=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_order(
+        &self,
+        side: OrderType,
+        price: f64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        expires_at: Option<u64>,
+        timestamp: Option<u64>,
+        owner: Option<String>,
+        strict: bool,
+        rounding: RoundingMode,
+        reduce_only: bool,
+        min_fill_quantity: Option<f64>,
+        matching_price: Option<f64>,
+        stp_group: Option<String>,
+    ) -> PyResult<Order> {
+        if price <= 0.0 && !self.allow_negative_prices {
+            return Err(OrderRejectReason::NonPositivePrice.error("Price must be positive"));
+        }
+        if quantity <= 0.0 {
+            return Err(OrderRejectReason::NonPositiveQuantity.error("Quantity must be positive"));
+        }
+
+        self.check_quantity(quantity)?;
+        if let Some(minimum) = min_fill_quantity {
+            if !(minimum > 0.0 && minimum <= quantity) {
+                return Err(OrderRejectReason::InvalidMinFillQuantity
+                    .error("min_fill_quantity must be positive and no greater than quantity"));
+            }
+            if time_in_force == TimeInForce::FOK {
+                return Err(OrderRejectReason::InvalidMinFillQuantity
+                    .error("FOK orders fill in full and take no min_fill_quantity"));
+            }
+        }
+
+        let price_in_ticks = self.round_price(price, strict, &rounding, &side)?;
+        let matching_price_in_ticks = match matching_price {
+            Some(matching_price) if matching_price <= 0.0 && !self.allow_negative_prices => {
+                return Err(
+                    OrderRejectReason::NonPositivePrice.error("Matching price must be positive")
+                );
+            }
+            Some(matching_price) => {
+                Some(self.round_price(matching_price, strict, &rounding, &side)?)
+            }
+            None => None,
+        };
+
+        let mut order = Order::build(
+            side,
+            price_in_ticks,
+            quantity,
+            time_in_force,
+            post_only,
+            expires_at,
+            timestamp,
+            owner,
+            matching_price_in_ticks,
+            stp_group,
+        )?;
+        order.reduce_only = reduce_only;
+        order.min_fill_quantity = min_fill_quantity;
+        Ok(order)
+    }
+
+    /// Creates an iceberg order (but does not add to the book) that only displays
+    /// `display_quantity` at a time, keeping the rest of `quantity` hidden.
+    #[pyo3(text_signature = "(self, side, price, quantity, display_quantity)")]
+    pub fn create_iceberg_order(
+        &self,
+        side: OrderType,
+        price: f64,
+        quantity: f64,
+        display_quantity: f64,
+    ) -> PyResult<Order> {
+        if display_quantity <= 0.0 || display_quantity > quantity {
+            return Err(OrderRejectReason::InvalidDisplayQuantity
+                .error("display_quantity must be positive and no greater than quantity"));
+        }
+        self.check_whole(display_quantity)?;
+
+        let mut order = self.create_order(
+            side,
+            price,
+            quantity,
+            TimeInForce::GTC,
+            false,
+            None,
+            None,
+            None,
+            false,
+            RoundingMode::Nearest,
+            false,
+            None,
+            None,
+            None,
+        )?;
+        order.display_quantity = Some(display_quantity);
+        order.hidden_quantity = quantity - display_quantity;
+        order.quantity = display_quantity;
+        Ok(order)
+    }
+
+    /// Creates a market order (but does not add to the book). Market orders
+    /// trade against any resting price and cancel any unfilled remainder.
+    ///
+    /// With `max_slippage_ticks` set, the order is collared: it only trades at
+    /// prices within that many ticks of the opposite touch (the best ask for
+    /// a buy, the best bid for a sell) as it stands when the order starts
+    /// matching, which is when it is added or, for a stop, when it triggers.
+    /// Once the next level is beyond the collar the sweep stops and the
+    /// remainder is canceled like any other unfilled market order.
+    #[pyo3(signature = (side, quantity, reduce_only=false, max_slippage_ticks=None))]
+    pub fn create_market_order(
+        &self,
+        side: OrderType,
+        quantity: f64,
+        reduce_only: bool,
+        max_slippage_ticks: Option<i64>,
+    ) -> PyResult<Order> {
+        if max_slippage_ticks.is_some_and(|ticks| ticks < 0) {
+            return Err(OrderRejectReason::NegativeSlippage
+                .error("max_slippage_ticks must not be negative"));
+        }
+        let mut order = Order::build(
+            side,
+            0,
+            quantity,
+            TimeInForce::IOC,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )?;
+        order.kind = OrderKind::Market;
+        order.reduce_only = reduce_only;
+        order.max_slippage_ticks = max_slippage_ticks;
+        Ok(order)
+    }
+
+    /// Creates a stop order (but does not add to the book) that stays dormant
+    /// until the last trade price crosses `stop_price`. Without a `limit_price`
+    /// it becomes a market order (stop-loss) once triggered, otherwise a limit
+    /// order at `limit_price` (stop-limit).
+    #[pyo3(signature = (side, stop_price, quantity, limit_price=None))]
+    pub fn create_stop_order(
+        &self,
+        side: OrderType,
+        stop_price: f64,
+        quantity: f64,
+        limit_price: Option<f64>,
+    ) -> PyResult<Order> {
+        if stop_price <= 0.0 && !self.allow_negative_prices {
+            return Err(OrderRejectReason::NonPositivePrice.error("stop_price must be positive"));
+        }
+
+        let mut order = match limit_price {
+            Some(price) => self.create_order(
+                side,
+                price,
+                quantity,
+                TimeInForce::GTC,
+                false,
+                None,
+                None,
+                None,
+                false,
+                RoundingMode::Nearest,
+                false,
+                None,
+                None,
+                None,
+            )?,
+            None => self.create_market_order(side, quantity, false, None)?,
+        };
+        order.stop_price_in_ticks = Some(self.price_to_ticks(stop_price));
+        Ok(order)
+    }
+
+    /// Creates a pegged limit order (but does not add to the book) whose price
+    /// tracks `reference` plus `offset_ticks`. The price is set when the order
+    /// is added and updated by `reprice_pegs`.
+    #[pyo3(signature = (side, reference, quantity, offset_ticks=0))]
+    pub fn create_peg_order(
+        &self,
+        side: OrderType,
+        reference: PegReference,
+        quantity: f64,
+        offset_ticks: i64,
+    ) -> PyResult<Order> {
+        self.check_quantity(quantity)?;
+        let mut order = Order::build(
+            side,
+            0,
+            quantity,
+            TimeInForce::GTC,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )?;
+        order.peg_reference = Some(reference);
+        order.peg_offset_ticks = offset_ticks;
+        Ok(order)
+    }
+
+    /// Moves every resting pegged order to its reference's current price plus
+    /// its offset, oldest first, and returns the resulting fills. A peg whose
+    /// price changes is re-queued at the back of its new level and matched if
+    /// it now crosses the book; one whose price is unchanged keeps its
+    /// priority. A peg whose reference side is empty stays where it is, as
+    /// does one whose new price `add` would reject: outside the price band,
+    /// off the tick multiple, or, under `CrossedBookPolicy.Reject`, leaving
+    /// the book locked or crossed. Under `Resolve`, a crossed book is resolved
+    /// as after an add. Call this after the market moves.
+    ///
+    /// # Errors
+    /// - Returns an error if the fill callback raises.
+    #[pyo3(text_signature = "($self)")]
+    pub fn reprice_pegs(slf: &Bound<'_, Self>) -> PyResult<Vec<Fill>> {
+        let fills = slf.borrow_mut().reprice_pegs_at(now_nanos())?;
+        Self::notify_fills(slf, &fills)?;
+        Ok(fills)
+    }
+
+    /// Starts an auction. Until `uncross` is called, added orders rest on the
+    /// book without matching, so the book may become crossed. Only `GTC` limit
+    /// orders are accepted; stop orders are held as usual.
+    #[pyo3(text_signature = "($self)")]
+    pub fn start_auction(&mut self) {
+        self.in_auction = true;
+        self.events.push(Event::StartAuction {});
+    }
+
+    /// Ends any auction and crosses the book at the single price that
+    /// maximizes the executed quantity, returning that price and the fills.
+    /// The price is None, with no fills, if the book does not cross.
+    ///
+    /// When several prices execute the same quantity, the one leaving the
+    /// smallest imbalance between the buy and sell quantity at that price is
+    /// used, then the one closest to the last trade price, then the lowest.
+    ///
+    /// Every crossing order then trades at the clearing price, matching bids
+    /// and asks in price-time priority. For self-trade prevention and the
+    /// fills' aggressor side, the later of each matched pair is treated as
+    /// the incoming order. Continuous matching resumes afterwards.
+    ///
+    /// # Errors
+    /// - Returns an error if the fill callback raises.
+    #[pyo3(text_signature = "($self)")]
+    pub fn uncross(slf: &Bound<'_, Self>) -> PyResult<(Option<f64>, Vec<Fill>)> {
+        let (price, fills) = slf.borrow_mut().uncross_at(now_nanos())?;
+        Self::notify_fills(slf, &fills)?;
+        Ok((price, fills))
+    }
+
+    /// Return whether the book is collecting orders for an auction
+    #[getter]
+    pub fn in_auction(&self) -> bool {
+        self.in_auction
+    }
+
+    /// Adds an order to the book, attempting to match it with resting orders.
+    /// Any unfilled remainder rests on the book for `GTC` orders and is
+    /// canceled for `IOC` orders. `FOK` orders execute only if they can be
+    /// filled in full, otherwise no fills occur and the book is left untouched.
+    /// Stop orders are held until the last trade price crosses their trigger,
+    /// and any stops triggered by this order's fills are executed in turn.
+    /// Pegged orders are priced from their reference when added.
+    ///
+    /// # Errors
+    /// - Returns an error if an order with the same ID is already tracked
+    ///   (resting, a pending stop, or filled). Canceled and expired orders are
+    ///   no longer tracked, so their IDs may be reused.
+    /// - Returns an error if a pegged order's reference price is unavailable.
+    /// - Returns an error if a market, `IOC` or `FOK` order is added during an auction.
+    /// - Returns an error if a post-only order would immediately match.
+    /// - Returns an error if the order's quantity violates the book's size limits.
+    /// - Returns an error if the order's price is not positive in a book
+    ///   without negative prices.
+    /// - Returns a `PriceBandError` if the order's price is outside the price band.
+    #[pyo3(text_signature = "(self, order)")]
+    pub fn add(slf: &Bound<'_, Self>, incoming_order: Order) -> PyResult<Vec<Fill>> {
+        Self::submit(slf, incoming_order, None)
+    }
+
+    /// Adds an order exactly like `add`, returning both the fills and the
+    /// order's remainder as it now rests on the book (or waits as an
+    /// untriggered stop). The remainder is None if the order was fully filled
+    /// or canceled.
+    ///
+    /// # Errors
+    /// - Returns the same errors as `add`.
+    #[pyo3(text_signature = "(self, order)")]
+    pub fn add_detailed(
+        slf: &Bound<'_, Self>,
+        incoming_order: Order,
+    ) -> PyResult<(Vec<Fill>, Option<Order>)> {
+        let order_id = incoming_order.id.clone();
+        let fills = Self::submit(slf, incoming_order, None)?;
+        let remainder = slf
+            .borrow()
+            .orders
+            .get(&order_id)
+            .filter(|order| order.is_open())
+            .cloned();
+        Ok((fills, remainder))
+    }
+
+    /// Adds an order exactly like `add`, but returns only a summary of the
+    /// fills as `(filled_quantity, average_price, fill_count)`: their total
+    /// quantity, volume-weighted average price and number. This avoids
+    /// creating a Python `Fill` object per fill. The average price is 0 if
+    /// nothing filled.
+    ///
+    /// # Errors
+    /// - Returns the same errors as `add`.
+    #[pyo3(text_signature = "(self, order)")]
+    pub fn add_summary(
+        slf: &Bound<'_, Self>,
+        incoming_order: Order,
+    ) -> PyResult<(f64, f64, usize)> {
+        let fills = Self::submit(slf, incoming_order, None)?;
+        let quantity: f64 = fills.iter().map(|fill| fill.quantity).sum();
+        let notional: f64 = fills.iter().map(|fill| fill.quantity * fill.price).sum();
+        let average_price = if quantity > 0.0 {
+            notional / quantity
+        } else {
+            0.0
+        };
+        Ok((quantity, average_price, fills.len()))
+    }
+
+    /// Adds an order exactly like `add`, but returns its fills grouped by the
+    /// price they executed at, as `(price, fills)` pairs in the order the
+    /// levels were consumed. Fills keep their execution order within each
+    /// group, and consecutive fills at the same price share a group.
+    ///
+    /// # Errors
+    /// - Returns the same errors as `add`.
+    #[pyo3(text_signature = "(self, order)")]
+    pub fn add_by_level(
+        slf: &Bound<'_, Self>,
+        incoming_order: Order,
+    ) -> PyResult<Vec<(f64, Vec<Fill>)>> {
+        let mut levels: Vec<(f64, Vec<Fill>)> = Vec::new();
+        for fill in Self::submit(slf, incoming_order, None)? {
+            match levels.last_mut() {
+                Some((price, fills)) if *price == fill.price => fills.push(fill),
+                _ => levels.push((fill.price, vec![fill])),
+            }
+        }
+        Ok(levels)
+    }
+
+    /// Adds each order in `orders` in sequence, exactly as if `add` were called
+    /// for each one, and returns the fills produced by each order. Every order
+    /// matches against the book as left by the orders before it.
+    ///
+    /// # Errors
+    /// - Returns the error of the first order that `add` rejects. Orders before
+    ///   it have already been applied and later orders are not processed.
+    #[pyo3(text_signature = "(self, orders)")]
+    pub fn add_many(slf: &Bound<'_, Self>, orders: Vec<Order>) -> PyResult<Vec<Vec<Fill>>> {
+        orders
+            .into_iter()
+            .map(|order| Self::submit(slf, order, None))
+            .collect()
+    }
+
+    /// Adds a group of linked orders (the legs of a spread, say) in list order
+    /// and returns every fill produced. Each leg matches against the book as
+    /// left by the legs before it, exactly as with `add_many`.
+    ///
+    /// With `all_or_none` set, the legs are first added in that order to a
+    /// scratch copy of the book, where the order filter vets each of them.
+    /// The group is committed only if every leg passes and is filled in full
+    /// there; otherwise no fills occur and the book is left untouched, like a
+    /// `FOK` order. Without it, the legs are added independently and may
+    /// partially fill or rest.
+    ///
+    /// # Errors
+    /// - Returns the error of the first leg that `add` rejects. With
+    ///   `all_or_none` set nothing has been applied; otherwise the legs before
+    ///   it have been applied and later legs are not processed.
+    #[pyo3(signature = (orders, all_or_none=false))]
+    pub fn add_group(
+        slf: &Bound<'_, Self>,
+        orders: Vec<Order>,
+        all_or_none: bool,
+    ) -> PyResult<Vec<Fill>> {
+        let timestamp = now_nanos();
+        if all_or_none {
+            // The scratch copy only tracks live orders, so IDs of filled orders
+            // are checked against the real book
+            let mut scratch = slf.borrow().scratch_copy();
+            let mut legs = Vec::with_capacity(orders.len());
+            for order in orders {
+                slf.borrow().check_new_id(&order.id)?;
+                let order = scratch.vet_order(order, timestamp)?;
+                Self::check_order_filter(slf, &order)?;
+                scratch.apply_order(order.clone(), timestamp)?;
+                let filled = scratch
+                    .orders
+                    .get(&order.id)
+                    .is_some_and(|o| o.status == OrderStatus::Filled);
+                if !filled {
+                    return Ok(Vec::new());
+                }
+                legs.push(order);
+            }
+
+            // Every leg has been vetted and filtered, so apply them as they were
+            let mut fills = Vec::new();
+            let mut book = slf.borrow_mut();
+            for order in legs {
+                fills.extend(book.apply_order(order, timestamp)?);
+            }
+            drop(book);
+            Self::notify_fills(slf, &fills)?;
+            return Ok(fills);
+        }
+
+        let mut fills = Vec::new();
+        for order in orders {
+            fills.extend(Self::submit(slf, order, Some(timestamp))?);
+        }
+        Ok(fills)
+    }
+
+    /// Merges `other` into this book by adding each of its resting orders and
+    /// untriggered stops, oldest first, through the normal matching path, so
+    /// orders that cross this book trade. Orders keep their IDs and remaining
+    /// quantities. Returns every fill produced; `other` is left unchanged.
+    ///
+    /// # Errors
+    /// - Returns an error if the books have different tick sizes.
+    /// - Returns an error if an order ID in `other` is already tracked by this
+    ///   book. This is checked before any order is added.
+    /// - Returns the error of the first order that `add` rejects. Orders before
+    ///   it have already been applied and later orders are not processed.
+    #[pyo3(text_signature = "(self, other)")]
+    pub fn merge(slf: &Bound<'_, Self>, other: &OrderBook) -> PyResult<Vec<Fill>> {
+        let this = slf.borrow();
+        if other.tick != this.tick {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Cannot merge a book with tick size {} into one with tick size {}",
+                other.tick_size(),
+                this.tick_size()
+            )));
+        }
+
+        let mut orders: Vec<Order> = other
+            .buy_orders
+            .values()
+            .chain(other.sell_orders.values())
+            .flatten()
+            .chain(&other.stop_orders)
+            .cloned()
+            .collect();
+        if let Some(order) = orders.iter().find(|o| this.orders.contains_key(&o.id)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Order {} is already tracked by this book",
+                order.id
+            )));
+        }
+        orders.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+        drop(this);
+
+        let mut fills = Vec::new();
+        for order in orders {
+            fills.extend(Self::submit(slf, order, None)?);
+        }
+        Ok(fills)
+    }
+
+    /// Places `orders` directly on the book as resting orders without running
+    /// them through matching, e.g. to rebuild a book from a known, uncrossed
+    /// state much faster than with `add`. Each order keeps its ID and
+    /// timestamp; orders at the same price queue in list order. The orders
+    /// are logged as adds, so replaying the event log rebuilds the same book.
+    ///
+    /// # Errors
+    /// - Returns an error if an order is not an open `GTC` limit order, e.g. a
+    ///   market, stop or pegged order.
+    /// - Returns an error if an order ID is already tracked or repeated in
+    ///   `orders`, or if a quantity breaks the book's size limits.
+    /// - Returns an error if a price is not positive in a book without negative
+    ///   prices, is outside the price band, or is not a multiple of the book's
+    ///   tick multiple.
+    /// - Returns an error if the orders would leave the book locked or
+    ///   crossed outside an auction.
+    ///
+    /// Everything is checked before any order is placed.
+    #[pyo3(text_signature = "(self, orders)")]
+    pub fn seed(&mut self, orders: Vec<Order>) -> PyResult<()> {
+        let mut ids = HashSet::new();
+        let mut best_bid = self.best_bid_level().map(|(price, _)| price);
+        let mut best_ask = self.best_ask_level().map(|(price, _)| price);
+        for order in &orders {
+            if order.kind == OrderKind::Market
+                || order.stop_price_in_ticks.is_some()
+                || order.peg_reference.is_some()
+                || order.time_in_force != TimeInForce::GTC
+                || order.status != OrderStatus::Open
+            {
+                return Err(OrderRejectReason::NotRestable.error(format!(
+                    "Order {} cannot be seeded; only open GTC limit orders can rest on the book",
+                    order.id
+                )));
+            }
+            if self.orders.contains_key(&order.id) || !ids.insert(&order.id) {
+                return Err(OrderRejectReason::DuplicateOrderId.error(format!(
+                    "Order {} has already been added to the book",
+                    order.id
+                )));
+            }
+            self.check_quantity(order.quantity + order.hidden_quantity)?;
+            self.check_whole(order.quantity)?;
+            self.check_positive_price(order)?;
+            self.check_price_band(order)?;
+            self.check_tick_multiple(order)?;
+            match order.side {
+                OrderType::Buy => best_bid = best_bid.max(Some(order.price_in_ticks)),
+                OrderType::Sell => {
+                    best_ask = Some(
+                        best_ask.map_or(order.price_in_ticks, |ask| ask.min(order.price_in_ticks)),
+                    )
+                }
+            }
+        }
+        if let (false, Some(bid), Some(ask)) = (self.in_auction, best_bid, best_ask) {
+            if bid >= ask {
+                return Err(OrderRejectReason::CrossedBook.error(format!(
+                    "Seeding would leave the best bid {} at or above the best ask {}",
+                    self.ticks_to_price(bid),
+                    self.ticks_to_price(ask)
+                )));
+            }
+        }
+
+        self.evicted.clear();
+        let timestamp = now_nanos();
+        for order in orders {
+            self.stats.orders_added += 1;
+            self.events.push(Event::Add {
+                order: order.clone(),
+                timestamp,
+            });
+            self.update_order(&order);
+            self.track_expiry(&order);
+            self.book_side_mut(&order.side)
+                .entry(order.price_in_ticks)
+                .or_default()
+                .push_back(order);
+        }
+        self.evict_excess();
+        Ok(())
+    }
+
+    /// Compares the orders tracked by this book with those tracked by `other`,
+    /// such as a recorded book and its replay. Orders are matched by ID and
+    /// are reported as modified when their remaining quantity (including any
+    /// hidden iceberg reserve) or status differ. Each list of IDs is sorted.
+    #[pyo3(text_signature = "(self, other)")]
+    pub fn diff(&self, other: &OrderBook) -> BookDiff {
+        let remaining = |order: &Order| order.quantity + order.hidden_quantity;
+
+        let mut added: Vec<String> = other
+            .orders
+            .keys()
+            .filter(|id| !self.orders.contains_key(*id))
+            .cloned()
+            .collect();
+        let mut removed = Vec::new();
+        let mut modified = Vec::new();
+        for (id, order) in &self.orders {
+            match other.orders.get(id) {
+                None => removed.push(id.clone()),
+                Some(theirs) => {
+                    if theirs.status != order.status
+                        || (remaining(theirs) - remaining(order)).abs() > QUANTITY_EPSILON
+                    {
+                        modified.push(id.clone());
+                    }
+                }
+            }
+        }
+
+        added.sort();
+        removed.sort();
+        modified.sort();
+        BookDiff {
+            added,
+            removed,
+            modified,
+        }
+    }
+
+    /// Adds an order to the book exactly like `add`, but stamps every
+    /// resulting fill with `timestamp` (in nanoseconds) instead of the
+    /// wall-clock time, so that simulations and tests are reproducible.
+    #[pyo3(text_signature = "(self, order, timestamp)")]
+    pub fn add_at(
+        slf: &Bound<'_, Self>,
+        incoming_order: Order,
+        timestamp: u64,
+    ) -> PyResult<Vec<Fill>> {
+        Self::submit(slf, incoming_order, Some(timestamp))
+    }
+
+    /// Returns the fills `order` would produce if it were added now, without
+    /// mutating the book. The order is matched exactly as `add` would match it,
+    /// against a copy of the opposite side of the book only, so stop orders it
+    /// would trigger are not simulated.
+    ///
+    /// # Errors
+    /// - Returns an error if `add` would reject the order.
+    #[pyo3(text_signature = "(self, order)")]
+    pub fn simulate(&self, mut order: Order) -> PyResult<Vec<Fill>> {
+        let mut scratch = self.empty_like();
+        match order.side {
+            OrderType::Buy => scratch.sell_orders = self.sell_orders.clone(),
+            OrderType::Sell => scratch.buy_orders = self.buy_orders.clone(),
+        }
+
+        self.price_peg(&mut order)?;
+        self.check_price_band(&order)?;
+        self.check_tick_multiple(&order)?;
+        if !order.stop_triggered(self.last_trade_ticks()) {
+            return Ok(Vec::new());
+        }
+        scratch.match_order(order, None)
+    }
+
+    /// Registers `callback` to be called with each `Fill` produced by `add`
+    /// and its variants, in execution order, including fills from triggered
+    /// stops. Callbacks run once the book has been fully updated and released,
+    /// so they can read the book and observe its final state, but must not
+    /// modify it. Pass None to remove the callback. Simulated and replayed
+    /// matches never invoke it, and it is not included in snapshots.
+    ///
+    /// If the callback raises, the remaining callbacks for that add are
+    /// skipped and the exception propagates out of `add`; the order itself
+    /// has already been applied to the book.
+    #[pyo3(signature = (callback))]
+    pub fn set_on_fill(&mut self, callback: Option<Py<PyAny>>) {
+        self.on_fill = callback;
+    }
+
+    /// Registers `predicate` to be called with each incoming `Order` before
+    /// `add` or one of its variants applies it, once the book's own checks
+    /// have passed. The predicate may read the book but must not modify it.
+    /// If it returns a falsy value the order is rejected with `FilteredOut`
+    /// and the book is left unchanged. Pass None to remove the filter.
+    /// Replayed adds never invoke it, and it is not included in snapshots.
+    ///
+    /// If the predicate raises, the exception propagates out of `add` and the
+    /// order is not applied.
+    #[pyo3(signature = (predicate))]
+    pub fn set_order_filter(&mut self, predicate: Option<Py<PyAny>>) {
+        self.order_filter = predicate;
+    }
+
+    /// Cancels an order by its ID, returning its final state (with status
+    /// `Canceled` and its remaining quantity), or None if it was not found or
+    /// could not be canceled; `cancel_detailed` reports which.
+    #[pyo3(text_signature = "(self, order_id)")]
+    pub fn cancel(&mut self, order_id: &str) -> Option<Order> {
+        match self.cancel_detailed(order_id) {
+            (CancelOutcome::Canceled, canceled_order) => canceled_order,
+            _ => None,
+        }
+    }
+
+    /// Cancels an order like `cancel`, returning a `CancelOutcome` along with
+    /// the order. Before anything is removed, the order is checked to be
+    /// queued at the price level recorded in the lookup map (or to be a
+    /// pending stop). If it is not, the book is left unchanged and the
+    /// tracked copy is returned with `FoundButNotInQueue`, so the
+    /// inconsistency surfaces instead of being silently dropped.
+    #[pyo3(text_signature = "(self, order_id)")]
+    pub fn cancel_detailed(&mut self, order_id: &str) -> (CancelOutcome, Option<Order>) {
+        let Some(order) = self.orders.get(order_id).filter(|order| order.is_open()) else {
+            return (CancelOutcome::NotFound, None);
+        };
+        let queued = self
+            .book_side(&order.side)
+            .get(&order.price_in_ticks)
+            .is_some_and(|queue| queue.iter().any(|o| o.id == order.id));
+        let pending_stop = self.stop_orders.iter().any(|o| o.id == order.id);
+        if !(queued || pending_stop) {
+            return (CancelOutcome::FoundButNotInQueue, Some(order.clone()));
+        }
+
+        let was_empty = self.is_empty_book();
+        let canceled_order = self.remove_order(order_id);
+        self.events.push(Event::Cancel {
+            order_id: order_id.to_string(),
+        });
+        self.stats.orders_canceled += 1;
+        self.track_emptied(was_empty);
+        (CancelOutcome::Canceled, canceled_order)
+    }
+
+    /// Resets the book in place to the state of a freshly constructed book,
+    /// dropping every order, fill and logged event while keeping the tick
+    /// size, matching configuration, fill callback and order filter. To only
+    /// discard the fill history, use `drain_fills`.
+    #[pyo3(text_signature = "($self)")]
+    pub fn clear(&mut self) {
+        let on_fill = self.on_fill.take();
+        let order_filter = self.order_filter.take();
+        *self = self.empty_like();
+        self.on_fill = on_fill;
+        self.order_filter = order_filter;
+    }
+
+    /// Cancels each order in `order_ids`, returning the IDs that were found and
+    /// canceled. Unknown IDs are skipped.
+    #[pyo3(text_signature = "(self, order_ids)")]
+    pub fn cancel_many(&mut self, order_ids: Vec<String>) -> Vec<String> {
+        order_ids
+            .into_iter()
+            .filter(|order_id| self.cancel(order_id).is_some())
+            .collect()
+    }
+
+    /// Cancels every resting and untriggered stop order on `side`, or on both
+    /// sides if `side` is None, returning the number of orders canceled.
+    #[pyo3(signature = (side=None))]
+    pub fn cancel_all(&mut self, side: Option<OrderType>) -> usize {
+        let sides = match side {
+            Some(side) => vec![side],
+            None => vec![OrderType::Buy, OrderType::Sell],
+        };
+
+        let was_empty = self.is_empty_book();
+        let mut canceled = 0;
+        for side in sides {
+            let book = std::mem::take(self.book_side_mut(&side));
+            let (stops, remaining): (Vec<Order>, Vec<Order>) =
+                std::mem::take(&mut self.stop_orders)
+                    .into_iter()
+                    .partition(|order| order.side == side);
+            self.stop_orders = remaining;
+
+            for order in book.values().flat_map(|queue| queue.iter()).chain(&stops) {
+                self.orders.remove(&order.id);
+                self.events.push(Event::Cancel {
+                    order_id: order.id.clone(),
+                });
+                canceled += 1;
+            }
+        }
+        self.stats.orders_canceled += canceled as u64;
+        self.track_emptied(was_empty);
+        canceled
+    }
+
+    /// Cancels every resting or untriggered stop order whose `expires_at` is at
+    /// or before `now` (in nanoseconds), returning the expired orders in
+    /// expiry order. Expiries are kept in a min-heap, so only orders that have
+    /// expired are visited: the cost is O(expired × log n), not O(n).
+    #[pyo3(text_signature = "(self, now)")]
+    pub fn expire(&mut self, now: u64) -> Vec<Order> {
+        let was_empty = self.is_empty_book();
+        let mut expired = Vec::new();
+
+        while let Some(Reverse((expires_at, _))) = self.expiries.peek() {
+            if *expires_at > now {
+                break;
+            }
+            let Some(Reverse((_, order_id))) = self.expiries.pop() else {
+                break;
+            };
+            // Entries for orders that have since filled or been canceled are stale
+            let live = self
+                .orders
+                .get(&order_id)
+                .is_some_and(|order| order.is_open() && order.is_expired(now));
+            if let Some(order) = live.then(|| self.remove_order(&order_id)).flatten() {
+                self.events.push(Event::Cancel { order_id });
+                expired.push(order);
+            }
+        }
+        self.stats.orders_canceled += expired.len() as u64;
+        self.track_emptied(was_empty);
+        expired
+    }
+
+    /// Returns the total visible quantity resting at `price` on `side`, or 0
+    /// if the level is empty. `price` is converted to ticks like `create_order`.
+    #[pyo3(text_signature = "(self, side, price)")]
+    pub fn quantity_at(&self, side: OrderType, price: f64) -> f64 {
+        let price_in_ticks = self.price_to_ticks(price);
+        self.book_side(&side)
+            .get(&price_in_ticks)
+            .map_or(0.0, level_quantity)
+    }
+
+    /// Returns the number of orders resting at `price` on `side`, or 0 if the
+    /// level is empty. `price` is converted to ticks like `create_order`.
+    #[pyo3(text_signature = "(self, side, price)")]
+    pub fn orders_at(&self, side: OrderType, price: f64) -> usize {
+        let price_in_ticks = self.price_to_ticks(price);
+        self.book_side(&side)
+            .get(&price_in_ticks)
+            .map_or(0, |queue| queue.len())
+    }
+
+    /// Returns the IDs of the orders resting at `price` on `side` in time
+    /// priority, which is the order FIFO matching with `LevelPriority.Time`
+    /// consumes them in, or an empty list if the level is empty. `price` is
+    /// converted to ticks like `create_order`.
+    #[pyo3(text_signature = "(self, side, price)")]
+    pub fn order_ids_at(&self, side: OrderType, price: f64) -> Vec<String> {
+        let price_in_ticks = self.price_to_ticks(price);
+        self.book_side(&side)
+            .get(&price_in_ticks)
+            .map_or_else(Vec::new, |queue| {
+                queue.iter().map(|order| order.id.clone()).collect()
+            })
+    }
+
+    /// Returns the number of occupied price levels on `side`.
+    #[pyo3(text_signature = "(self, side)")]
+    pub fn num_levels(&self, side: OrderType) -> usize {
+        self.book_side(&side).len()
+    }
+
+    /// Returns the number of individual orders resting on `side`, across all
+    /// of its price levels.
+    #[pyo3(text_signature = "(self, side)")]
+    pub fn num_orders(&self, side: OrderType) -> usize {
+        self.book_side(&side).values().map(VecDeque::len).sum()
+    }
+
+    /// Returns the net filled quantity of `owner`: bought minus sold across
+    /// every fill since the book was created or cleared. Reduce-only orders
+    /// are capped against this position when they are added.
+    #[pyo3(text_signature = "(self, owner)")]
+    pub fn position(&self, owner: &str) -> f64 {
+        self.positions.get(owner).copied().unwrap_or(0.0)
+    }
+
+    /// Returns copies of every open order belonging to `owner`, both resting
+    /// and untriggered stops, oldest first.
+    #[pyo3(text_signature = "(self, owner)")]
+    pub fn orders_by_owner(&self, owner: &str) -> Vec<Order> {
+        let mut orders: Vec<Order> = self
+            .orders
+            .values()
+            .filter(|order| order.is_open() && order.owner.as_deref() == Some(owner))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.timestamp);
+        orders
+    }
+
+    /// Sums the resting quantity an incoming order on `side` could match against
+    /// at prices no worse than `limit_ticks`, without mutating the book.
+    /// Hidden iceberg reserves are included since they are executable.
+    #[pyo3(text_signature = "(self, side, limit_ticks)")]
+    pub fn available_liquidity(&self, side: OrderType, limit_ticks: i64) -> f64 {
+        match side {
+            OrderType::Buy => self
+                .sell_orders
+                .range(..=limit_ticks)
+                .flat_map(|(_, queue)| queue.iter())
+                .map(|order| order.quantity + order.hidden_quantity)
+                .sum(),
+            OrderType::Sell => self
+                .buy_orders
+                .range(limit_ticks..)
+                .flat_map(|(_, queue)| queue.iter())
+                .map(|order| order.quantity + order.hidden_quantity)
+                .sum(),
+        }
+    }
+
+    /// Returns the total quantity an aggressor on `side` willing to trade up to
+    /// `price_limit` could reach, like `available_liquidity` with the limit
+    /// given as a price. `price_limit` is converted to ticks like
+    /// `create_order`, and an empty opposite side has no liquidity.
+    #[pyo3(text_signature = "(self, side, price_limit)")]
+    pub fn liquidity_within(&self, side: OrderType, price_limit: f64) -> f64 {
+        self.available_liquidity(side, self.price_to_ticks(price_limit))
+    }
+
+    /// Returns the volume-weighted average price an incoming order on `side`
+    /// would pay to immediately trade `quantity`, walking the opposite side
+    /// from the touch without mutating the book. Hidden iceberg reserves are
+    /// included since they are executable. Returns None if the book cannot
+    /// fill the whole quantity.
+    #[pyo3(text_signature = "(self, side, quantity)")]
+    pub fn impact_price(&self, side: OrderType, quantity: f64) -> Option<f64> {
+        if quantity <= 0.0 {
+            return None;
+        }
+
+        let levels: Vec<(&i64, &VecDeque<Order>)> = match side {
+            OrderType::Buy => self.sell_orders.iter().collect(),
+            OrderType::Sell => self.buy_orders.iter().rev().collect(),
+        };
+
+        let mut remaining = quantity;
+        let mut notional = 0.0;
+        for (price, queue) in levels {
+            let available: f64 = queue
+                .iter()
+                .map(|order| order.quantity + order.hidden_quantity)
+                .sum();
+            // The last level may only be partially consumed
+            let taken = available.min(remaining);
+            notional += taken * self.ticks_to_price(*price);
+            remaining -= taken;
+            if remaining <= 0.0 {
+                return Some(notional / quantity);
+            }
+        }
+        None
+    }
+
+    /// Changes the quantity of a resting order. Reducing the quantity keeps the
+    /// order's time priority, while increasing it moves the order to the back
+    /// of its price level. With `reject_increase` set, an increase is refused
+    /// instead, for callers that treat any size increase as a new order.
+    /// Returns `false` if the order is not resting.
+    ///
+    /// # Errors
+    /// - Returns an error if `new_quantity` is non-positive, or if the new total
+    ///   with any hidden reserve violates the book's size limits.
+    /// - Returns an error if `reject_increase` is set and `new_quantity`
+    ///   exceeds the order's current quantity.
+    #[pyo3(signature = (order_id, new_quantity, reject_increase=false))]
+    #[pyo3(text_signature = "(self, order_id, new_quantity, reject_increase=False)")]
+    pub fn modify_quantity(
+        &mut self,
+        order_id: &str,
+        new_quantity: f64,
+        reject_increase: bool,
+    ) -> PyResult<bool> {
+        if new_quantity <= 0.0 {
+            return Err(
+                OrderRejectReason::NonPositiveQuantity.error("new_quantity must be positive")
+            );
+        }
+
+        let Some(order) = self.orders.get(order_id) else {
+            return Ok(false);
+        };
+        // Size limits apply to the whole order, hidden reserve included, as in `submit`
+        self.check_quantity(new_quantity + order.hidden_quantity)?;
+        self.check_whole(new_quantity)?;
+        let (side, price_ticks) = (order.side.clone(), order.price_in_ticks);
+        let Some(queue) = self.book_side_mut(&side).get_mut(&price_ticks) else {
+            return Ok(false);
+        };
+        let Some(index) = queue.iter().position(|o| o.id == order_id) else {
+            return Ok(false);
+        };
+
+        let loses_priority = new_quantity > queue[index].quantity;
+        if loses_priority && reject_increase {
+            return Err(OrderRejectReason::QuantityIncrease
+                .error("Quantity increases are rejected; submit a new order instead"));
+        }
+        queue[index].quantity = new_quantity;
+        let updated = queue[index].clone();
+        if loses_priority {
+            let order = queue.remove(index).expect("Index was found in the queue");
+            queue.push_back(order);
+        }
+
+        // Keep the copy in `orders` consistent with the queue
+        self.update_order(&updated);
+        self.events.push(Event::Modify {
+            order_id: order_id.to_string(),
+            new_quantity,
+        });
+        Ok(true)
+    }
+
+    /// Pulls `amount` from a resting order's size without canceling it. The
+    /// order keeps its time priority; an iceberg's hidden reserve is reduced
+    /// before its visible quantity. If nothing is left, the order is canceled
+    /// instead. Returns the updated order, or the canceled order.
+    ///
+    /// # Errors
+    /// - Returns an error if `amount` is non-positive, or fractional in a book
+    ///   with integer quantities.
+    /// - Returns an error if the order is not resting on the book.
+    /// - Returns an error if `amount` exceeds the order's remaining quantity.
+    #[pyo3(text_signature = "(self, order_id, amount)")]
+    pub fn reduce(&mut self, order_id: &str, amount: f64) -> PyResult<Order> {
+        if amount <= 0.0 {
+            return Err(OrderRejectReason::NonPositiveQuantity.error("amount must be positive"));
+        }
+        self.check_whole(amount)?;
+        let Some(order) = self.resting_order(order_id) else {
+            return Err(OrderRejectReason::NotResting.error("Order is not resting on the book"));
+        };
+
+        let remaining = order.quantity + order.hidden_quantity;
+        if amount > remaining + QUANTITY_EPSILON {
+            return Err(OrderRejectReason::ReductionTooLarge.error(format!(
+                "Cannot reduce order {} by {}; only {} remains",
+                order_id, amount, remaining
+            )));
+        }
+        if amount >= remaining - QUANTITY_EPSILON {
+            return Ok(self.cancel(order_id).expect("Order is resting on the book"));
+        }
+
+        let queue = self
+            .book_side_mut(&order.side)
+            .get_mut(&order.price_in_ticks)
+            .expect("Order is resting on the book");
+        let resting = queue
+            .iter_mut()
+            .find(|o| o.id == order_id)
+            .expect("Order is resting on the book");
+        let from_hidden = amount.min(resting.hidden_quantity);
+        resting.hidden_quantity -= from_hidden;
+        resting.reduce_by(amount - from_hidden);
+        let updated = resting.clone();
+
+        // Keep the copy in `orders` consistent with the queue
+        self.update_order(&updated);
+        self.events.push(Event::Reduce {
+            order_id: order_id.to_string(),
+            amount,
+        });
+        Ok(updated)
+    }
+
+    /// Replaces a resting order with a new price and quantity using standard
+    /// cancel-replace semantics. A quantity decrease at the same price keeps
+    /// the order's time priority. Any price change or quantity increase
+    /// cancels the old order and submits it afresh (same ID, new timestamp,
+    /// back of the new level), which may immediately match. A pegged order
+    /// moved to a new price becomes a plain limit order. With `reject_increase`
+    /// set, a quantity increase is refused and the original order is left
+    /// untouched. Returns the resulting order.
+    ///
+    /// # Errors
+    /// - Returns an error if the order is not resting on the book.
+    /// - Returns an error if `new_price` or `new_quantity` is non-positive.
+    /// - Returns an error if `reject_increase` is set and `new_quantity`
+    ///   exceeds the order's current quantity.
+    /// - Returns an error if `add` would reject the replacement, in which case
+    ///   the original order is left resting in its place in the queue.
+    #[pyo3(signature = (order_id, new_price, new_quantity, reject_increase=false))]
+    #[pyo3(text_signature = "(self, order_id, new_price, new_quantity, reject_increase=False)")]
+    pub fn replace(
+        slf: &Bound<'_, Self>,
+        order_id: &str,
+        new_price: f64,
+        new_quantity: f64,
+        reject_increase: bool,
+    ) -> PyResult<Order> {
+        let mut this = slf.borrow_mut();
+        let Some(old_order) = this.resting_order(order_id) else {
+            return Err(OrderRejectReason::NotResting.error("Order is not resting on the book"));
+        };
+        if reject_increase && new_quantity > old_order.quantity {
+            return Err(OrderRejectReason::QuantityIncrease
+                .error("Quantity increases are rejected; submit a new order instead"));
+        }
+
+        // Validate and convert the new price exactly like `create_order`
+        let template = this.create_order(
+            old_order.side.clone(),
+            new_price,
+            new_quantity,
+            TimeInForce::GTC,
+            false,
+            None,
+            None,
+            None,
+            false,
+            RoundingMode::Nearest,
+            false,
+            None,
+            None,
+            None,
+        )?;
+
+        if template.price_in_ticks == old_order.price_in_ticks && new_quantity <= old_order.quantity
+        {
+            this.modify_quantity(order_id, new_quantity, false)?;
+            drop(this);
+        } else {
+            // Take the original off the book, keeping its place in the queue,
+            // and put it back if the replacement does not pass every check
+            let index = this
+                .queue_position(order_id)
+                .map(|(index, _)| index)
+                .expect("Order is resting on the book");
+            let was_empty = this.is_empty_book();
+            this.remove_order(order_id);
+            let mut new_order = old_order.clone();
+            new_order.price_in_ticks = template.price_in_ticks;
+            new_order.quantity = new_quantity;
+            new_order.timestamp = template.timestamp;
+            new_order.status = OrderStatus::Open;
+            new_order.peg_reference = None;
+            let vetted = this.vet_order(new_order, template.timestamp);
+            drop(this);
+            let vetted = vetted.and_then(|order| {
+                Self::check_order_filter(slf, &order)?;
+                Ok(order)
+            });
+
+            let mut this = slf.borrow_mut();
+            let new_order = match vetted {
+                Ok(order) => order,
+                Err(err) => {
+                    this.restore_order(old_order, index);
+                    return Err(err);
+                }
+            };
+            this.events.push(Event::Cancel {
+                order_id: order_id.to_string(),
+            });
+            this.stats.orders_canceled += 1;
+            this.track_emptied(was_empty);
+            let fills = this.apply_order(new_order, template.timestamp)?;
+            drop(this);
+            Self::notify_fills(slf, &fills)?;
+        }
+
+        Ok(slf
+            .borrow()
+            .orders
+            .get(order_id)
+            .cloned()
+            .expect("Replaced order is tracked in `orders`"))
+    }
+
+    /// Retrieves an order by its ID. Returns None if the order is not found.
+    #[pyo3(text_signature = "(self, order_id)")]
+    pub fn get_order(&self, order_id: &str) -> Option<Order> {
+        self.orders.get(order_id).cloned()
+    }
+
+    /// Returns the queue position of a resting order as `(index, quantity_ahead)`:
+    /// the zero-based index of the order within its price level and the
+    /// displayed quantity of the orders ahead of it. Returns None if the order
+    /// is not resting.
+    #[pyo3(text_signature = "(self, order_id)")]
+    pub fn queue_position(&self, order_id: &str) -> Option<(usize, f64)> {
+        let order = self.orders.get(order_id)?;
+        let queue = match order.side {
+            OrderType::Buy => self.buy_orders.get(&order.price_in_ticks)?,
+            OrderType::Sell => self.sell_orders.get(&order.price_in_ticks)?,
+        };
+        let index = queue.iter().position(|o| o.id == order_id)?;
+        let ahead = queue.iter().take(index).map(|o| o.quantity).sum();
+        Some((index, ahead))
+    }
+
+    /// Helper method to update an order in the `orders` map.
+    fn update_order(&mut self, order: &Order) {
+        self.orders.insert(order.id.clone(), order.clone());
+    }
+
+    /// Returns a lazy iterator over the resting orders on `side` in price-time
+    /// priority: best price first, and oldest first within a price level.
+    #[pyo3(text_signature = "($self, side)")]
+    pub fn iter_side(slf: PyRef<'_, Self>, side: OrderType) -> OrderIterator {
+        OrderIterator {
+            book: slf.into(),
+            side,
+            cursor: None,
+        }
+    }
+
+    /// Returns the first `n` resting orders on `side` in price-time priority,
+    /// cloning only the orders returned.
+    #[pyo3(text_signature = "($self, side, n)")]
+    pub fn top_orders(&self, side: OrderType, n: usize) -> Vec<Order> {
+        let levels: Box<dyn Iterator<Item = &VecDeque<Order>>> = match side {
+            OrderType::Buy => Box::new(self.buy_orders.values().rev()),
+            OrderType::Sell => Box::new(self.sell_orders.values()),
+        };
+        levels.flatten().take(n).cloned().collect()
+    }
+
+    /// Get a list of all buy orders
+    #[getter]
+    pub fn get_buy_orders(&self) -> Vec<Order> {
+        self.buy_orders
+            .values()
+            .flat_map(|queue| queue.iter().cloned())
+            .collect()
+    }
+
+    /// Get a list of all sell orders
+    #[getter]
+    pub fn get_sell_orders(&self) -> Vec<Order> {
+        self.sell_orders
+            .values()
+            .flat_map(|queue| queue.iter().cloned())
+            .collect()
+    }
+
+    /// Get a list of all untriggered stop orders, in submission order
+    #[getter]
+    pub fn get_stop_orders(&self) -> Vec<Order> {
+        self.stop_orders.clone()
+    }
+
+    /// Returns the top `n` bid and ask levels as `(price, total_quantity)` pairs,
+    /// with bids in descending and asks in ascending price order. Fewer than
+    /// `n` levels are returned if the book is not that deep.
+    #[pyo3(text_signature = "(self, n)")]
+    pub fn depth(&self, n: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        let bids = self
+            .buy_orders
+            .iter()
+            .rev()
+            .take(n)
+            .map(|(price, queue)| (self.ticks_to_price(*price), level_quantity(queue)))
+            .collect();
+        let asks = self
+            .sell_orders
+            .iter()
+            .take(n)
+            .map(|(price, queue)| (self.ticks_to_price(*price), level_quantity(queue)))
+            .collect();
+        (bids, asks)
+    }
+
+    /// Returns the `(price, total_quantity)` of the level `depth` levels from
+    /// the touch on `side`, where 0 is the best level, without building the
+    /// other levels. Returns None if the side is not that deep.
+    #[pyo3(text_signature = "(self, side, depth)")]
+    pub fn level(&self, side: OrderType, depth: usize) -> Option<PriceLevel> {
+        let level = match side {
+            OrderType::Buy => self.buy_orders.iter().rev().nth(depth),
+            OrderType::Sell => self.sell_orders.iter().nth(depth),
+        };
+        level.map(|(price, queue)| (self.ticks_to_price(*price), level_quantity(queue)))
+    }
+
+    /// Returns every bid and ask level as a dict mapping price to total
+    /// quantity. The dicts are built directly from the aggregated levels, with
+    /// bids inserted in descending and asks in ascending price order.
+    #[pyo3(text_signature = "($self)")]
+    pub fn ladder<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyDict>, Bound<'py, PyDict>)> {
+        let bids = PyDict::new(py);
+        for (price, queue) in self.buy_orders.iter().rev() {
+            bids.set_item(self.ticks_to_price(*price), level_quantity(queue))?;
+        }
+        let asks = PyDict::new(py);
+        for (price, queue) in &self.sell_orders {
+            asks.set_item(self.ticks_to_price(*price), level_quantity(queue))?;
+        }
+        Ok((bids, asks))
+    }
+
+    /// Returns the levels on `side` from the touch outward as `(price,
+    /// cumulative_quantity)` pairs, where each quantity includes every
+    /// better-priced level. Bids run down in price and asks up; an empty side
+    /// yields an empty list.
+    #[pyo3(text_signature = "($self, side)")]
+    pub fn cumulative_depth(&self, side: OrderType) -> Vec<PriceLevel> {
+        let levels: Box<dyn Iterator<Item = (&i64, &VecDeque<Order>)>> = match side {
+            OrderType::Buy => Box::new(self.buy_orders.iter().rev()),
+            OrderType::Sell => Box::new(self.sell_orders.iter()),
+        };
+        levels
+            .scan(0.0, |total, (price, queue)| {
+                *total += level_quantity(queue);
+                Some((self.ticks_to_price(*price), *total))
+            })
+            .collect()
+    }
+
+    /// Returns the resting quantity grouped into price buckets `bucket_ticks`
+    /// wide, as `(start_tick, buy_quantity, sell_quantity)` triples in
+    /// ascending price order. A bucket covers the ticks from its start up to
+    /// the next multiple of `bucket_ticks`. Empty buckets are skipped.
+    ///
+    /// # Errors
+    /// - Returns an error if `bucket_ticks` is not positive.
+    #[pyo3(text_signature = "($self, bucket_ticks)")]
+    pub fn quantity_histogram(&self, bucket_ticks: i64) -> PyResult<Vec<(i64, f64, f64)>> {
+        if bucket_ticks <= 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "bucket_ticks must be positive",
+            ));
+        }
+
+        let mut buckets: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+        for (price, queue) in &self.buy_orders {
+            let start = price.div_euclid(bucket_ticks) * bucket_ticks;
+            buckets.entry(start).or_default().0 += level_quantity(queue);
+        }
+        for (price, queue) in &self.sell_orders {
+            let start = price.div_euclid(bucket_ticks) * bucket_ticks;
+            buckets.entry(start).or_default().1 += level_quantity(queue);
+        }
+        Ok(buckets
+            .into_iter()
+            .map(|(start, (buy, sell))| (start, buy, sell))
+            .collect())
+    }
+
+    /// Get the best (highest) bid as `(price, total_quantity)`, or None if there are no bids
+    #[getter]
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.best_bid_level()
+            .map(|(price, quantity)| (self.ticks_to_price(price), quantity))
+    }
+
+    /// Get the best (lowest) ask as `(price, total_quantity)`, or None if there are no asks
+    #[getter]
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.best_ask_level()
+            .map(|(price, quantity)| (self.ticks_to_price(price), quantity))
+    }
+
+    /// Calculate the mid-price between the best bid and best ask.
+    /// Returns None if there are no orders on either side.
+    /// The mid-price is returned in the same units as the prices (not ticks).
+    #[pyo3(text_signature = "($self)")]
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the best bid is priced above the best ask. Matching
+    /// never leaves the book crossed outside an auction, so this indicates a
+    /// bug. Only the two best levels are compared.
+    #[pyo3(text_signature = "($self)")]
+    pub fn is_crossed(&self) -> bool {
+        match (self.best_bid_level(), self.best_ask_level()) {
+            (Some((bid, _)), Some((ask, _))) => bid > ask,
+            _ => false,
+        }
+    }
+
+    /// Returns the current best bid and offer with their total level
+    /// quantities as a single `Quote`, stamped with the wall-clock time.
+    /// Returns None only if both sides are empty.
+    #[pyo3(text_signature = "($self)")]
+    pub fn quote(&self) -> Option<Quote> {
+        let bid = self.best_bid();
+        let ask = self.best_ask();
+        if bid.is_none() && ask.is_none() {
+            return None;
+        }
+        Some(Quote {
+            bid_price: bid.map(|(price, _)| price),
+            bid_size: bid.map_or(0.0, |(_, size)| size),
+            ask_price: ask.map(|(price, _)| price),
+            ask_size: ask.map_or(0.0, |(_, size)| size),
+            timestamp: now_nanos(),
+        })
+    }
+
+    /// Calculate the size-weighted mid-price (microprice) from the best bid
+    /// and ask and their total level quantities. It leans toward the side
+    /// with less size, where the next trade is more likely to occur. Returns
+    /// None if there are no orders on either side.
+    #[pyo3(text_signature = "($self)")]
+    pub fn microprice(&self) -> Option<f64> {
+        let (bid, bid_size) = self.best_bid()?;
+        let (ask, ask_size) = self.best_ask()?;
+        Some((bid * ask_size + ask * bid_size) / (bid_size + ask_size))
+    }
+
+    /// Helper method to calculate total buy volume
+    fn buy_volume(&self) -> f64 {
+        self.buy_orders
+            .values()
+            .flat_map(|queue| queue.iter())
+            .map(|order| order.quantity)
+            .sum()
+    }
+
+    /// Helper method to calculate total sell volume
+    fn sell_volume(&self) -> f64 {
+        self.sell_orders
+            .values()
+            .flat_map(|queue| queue.iter())
+            .map(|order| order.quantity)
+            .sum()
+    }
+
+    /// Get the total resting buy notional (price × visible quantity)
+    #[getter]
+    pub fn buy_notional(&self) -> f64 {
+        self.notional(&self.buy_orders)
+    }
+
+    /// Get the total resting sell notional (price × visible quantity)
+    #[getter]
+    pub fn sell_notional(&self) -> f64 {
+        self.notional(&self.sell_orders)
+    }
+
+    /// Returns the size-weighted average price of every resting order on
+    /// `side`, across all of its levels: its notional divided by its visible
+    /// quantity. Returns None if the side is empty.
+    #[pyo3(text_signature = "(self, side)")]
+    pub fn avg_price(&self, side: OrderType) -> Option<f64> {
+        let book = self.book_side(&side);
+        let quantity: f64 = book.values().map(level_quantity).sum();
+        if quantity <= 0.0 {
+            return None;
+        }
+        Some(self.notional(book) / quantity)
+    }
+
+    /// Calculate the current spread in the order book.
+    /// Returns None if there are no orders on either side.
+    /// The spread is returned in the same units as the prices (not ticks).
+    #[pyo3(text_signature = "($self)")]
+    fn spread(&self) -> Option<f64> {
+        // Convert from tick difference to price difference
+        self.spread_ticks()
+            .map(|spread_in_ticks| self.ticks_to_price(spread_in_ticks))
+    }
+
+    /// Calculate the current spread in ticks: the best ask minus the best bid,
+    /// exactly, without converting to a price. Returns None if there are no
+    /// orders on either side, like `spread`.
+    #[pyo3(text_signature = "($self)")]
+    pub fn spread_ticks(&self) -> Option<i64> {
+        match (self.best_bid_level(), self.best_ask_level()) {
+            (Some((bid_price, _)), Some((ask_price, _))) => Some(ask_price - bid_price),
+            _ => None,
+        }
+    }
+
+    /// Return the self-trade prevention policy
+    #[getter]
+    pub fn stp_policy(&self) -> SelfTradePrevention {
+        self.stp_policy.clone()
+    }
+
+    /// Return the minimum order quantity, if any
+    #[getter]
+    pub fn min_quantity(&self) -> Option<f64> {
+        self.min_quantity
+    }
+
+    /// Return the maximum order quantity, if any
+    #[getter]
+    pub fn max_quantity(&self) -> Option<f64> {
+        self.max_quantity
+    }
+
+    /// Return whether orders may have zero or negative prices
+    #[getter]
+    pub fn allow_negative_prices(&self) -> bool {
+        self.allow_negative_prices
+    }
+
+    /// Return the number of ticks that limit prices must be a multiple of
+    #[getter]
+    pub fn tick_multiple(&self) -> i64 {
+        self.tick_multiple
+    }
+
+    /// Return the price band in ticks around the last trade price, if any
+    #[getter]
+    pub fn price_band_ticks(&self) -> Option<i64> {
+        self.price_band_ticks
+    }
+
+    /// Return the lot size, if any
+    #[getter]
+    pub fn lot_size(&self) -> Option<f64> {
+        self.lot_size
+    }
+
+    /// Return the fill price rule
+    #[getter]
+    pub fn fill_price_rule(&self) -> FillPriceRule {
+        self.fill_price_rule.clone()
+    }
+
+    /// Return the fee charged to the resting side of each fill, in basis points
+    #[getter]
+    pub fn maker_fee_bps(&self) -> f64 {
+        self.maker_fee_bps
+    }
+
+    /// Return the fee charged to the incoming side of each fill, in basis points
+    #[getter]
+    pub fn taker_fee_bps(&self) -> f64 {
+        self.taker_fee_bps
+    }
+
+    /// Return whether order quantities must be whole numbers
+    #[getter]
+    pub fn quantity_is_integer(&self) -> bool {
+        self.quantity_is_integer
+    }
+
+    /// Return the priority used to pick the next resting order within a level
+    #[getter]
+    pub fn level_priority(&self) -> LevelPriority {
+        self.level_priority.clone()
+    }
+
+    /// Return the matching mode
+    #[getter]
+    pub fn matching_mode(&self) -> MatchingMode {
+        self.matching_mode.clone()
+    }
+
+    /// Get the orders canceled by self-trade prevention during the most recent add
+    #[getter]
+    pub fn self_trade_canceled(&self) -> Vec<Order> {
+        self.self_trade_canceled.clone()
+    }
+
+    /// Get the orders evicted by the `max_orders` cap during the most recent add
+    #[getter]
+    pub fn evicted(&self) -> Vec<Order> {
+        self.evicted.clone()
+    }
+
+    /// Return the cap on resting orders, if any
+    #[getter]
+    pub fn max_orders(&self) -> Option<usize> {
+        self.max_orders
+    }
+
+    /// Return how an add that would leave the book crossed is handled
+    #[getter]
+    pub fn crossed_book_policy(&self) -> CrossedBookPolicy {
+        self.crossed_book_policy.clone()
+    }
+
+    /// Get the price of the most recent fill, or None if nothing has traded
+    #[getter]
+    pub fn last_trade_price(&self) -> Option<f64> {
+        self.last_trade.as_ref().map(|fill| fill.price)
+    }
+
+    /// Get the quantity of the most recent fill, or None if nothing has traded
+    #[getter]
+    pub fn last_trade_quantity(&self) -> Option<f64> {
+        self.last_trade.as_ref().map(|fill| fill.quantity)
+    }
+
+    /// Get the timestamp of the most recent fill, or None if nothing has traded
+    #[getter]
+    pub fn last_trade_timestamp(&self) -> Option<u64> {
+        self.last_trade.as_ref().map(|fill| fill.timestamp)
+    }
+
+    /// Return the tick size for informational purposes
+    #[getter]
+    pub fn tick_size(&self) -> f64 {
+        self.tick.tick_size()
+    }
+
+    /// Converts a price in ticks to a price. Every price the book reports is
+    /// converted this way, exactly rather than by multiplying floats.
+    #[pyo3(text_signature = "($self, ticks)")]
+    pub fn ticks_to_price(&self, ticks: i64) -> f64 {
+        self.tick.to_price(ticks)
+    }
+
+    /// Converts a price to the nearest whole number of ticks, exactly as
+    /// `create_order` and every other price argument are converted.
+    #[pyo3(text_signature = "($self, price)")]
+    pub fn price_to_ticks(&self, price: f64) -> i64 {
+        self.tick.exact_ticks(price).round() as i64
+    }
+
+    /// Returns the book's activity counters as a dict: `orders_added`,
+    /// `orders_canceled` (including expiries), `fills`, `volume` and
+    /// `times_emptied`, the number of adds and cancels that left the book
+    /// with no resting orders, and `crosses_resolved`, the number of adds that
+    /// left the book locked or crossed until resolved. Counters run from the
+    /// book's creation, the last `clear`, or the last `reset_stats`, and are
+    /// not included in snapshots.
+    #[pyo3(text_signature = "($self)")]
+    pub fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = PyDict::new(py);
+        stats.set_item("orders_added", self.stats.orders_added)?;
+        stats.set_item("orders_canceled", self.stats.orders_canceled)?;
+        stats.set_item("fills", self.stats.fills)?;
+        stats.set_item("volume", self.stats.volume)?;
+        stats.set_item("times_emptied", self.stats.times_emptied)?;
+        stats.set_item("crosses_resolved", self.stats.crosses_resolved)?;
+        Ok(stats)
+    }
+
+    /// Resets every counter reported by `stats` to zero.
+    #[pyo3(text_signature = "($self)")]
+    pub fn reset_stats(&mut self) {
+        self.stats = BookStats::default();
+    }
+
+    /// Returns the number of fills the book has produced.
+    #[pyo3(text_signature = "($self)")]
+    pub fn trade_count(&self) -> usize {
+        self.fills.len()
+    }
+
+    /// Returns the total quantity traded across every fill the book has produced.
+    #[pyo3(text_signature = "($self)")]
+    pub fn total_traded_volume(&self) -> f64 {
+        self.fills.iter().map(|fill| fill.quantity).sum()
+    }
+
+    /// Returns every fill in which the order with `order_id` took part, as
+    /// either the buy or the sell side, in execution order. This is a linear
+    /// scan, O(n) in the total number of fills the book has produced.
+    #[pyo3(text_signature = "(self, order_id)")]
+    pub fn fills_for_order(&self, order_id: &str) -> Vec<Fill> {
+        self.fills
+            .iter()
+            .filter(|fill| fill.buy_id == order_id || fill.sell_id == order_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Exports every fill the book has produced as CSV, with a header row and
+    /// one line per fill in execution order. Floats are written in their
+    /// shortest form that parses back to the exact same value. Order IDs are
+    /// quoted per RFC 4180 when they contain a comma, quote or line break.
+    #[pyo3(text_signature = "($self)")]
+    pub fn fills_to_csv(&self) -> String {
+        let mut csv = String::from("quantity,price,buy_id,sell_id,timestamp\n");
+        for fill in &self.fills {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                fill.quantity,
+                fill.price,
+                csv_field(&fill.buy_id),
+                csv_field(&fill.sell_id),
+                fill.timestamp
+            ));
+        }
+        csv
+    }
+
+    /// Returns every fill the book has produced as column vectors `(quantity,
+    /// price, buy_id, sell_id, timestamp)`, in execution order. This is a
+    /// columnar alternative to iterating `Fill` objects that can be handed to
+    /// pandas or Arrow without creating a Python object per fill.
+    #[pyo3(text_signature = "($self)")]
+    pub fn fills_to_columns(&self) -> FillColumns {
+        let count = self.fills.len();
+        let mut quantities = Vec::with_capacity(count);
+        let mut prices = Vec::with_capacity(count);
+        let mut buy_ids = Vec::with_capacity(count);
+        let mut sell_ids = Vec::with_capacity(count);
+        let mut timestamps = Vec::with_capacity(count);
+        for fill in &self.fills {
+            quantities.push(fill.quantity);
+            prices.push(fill.price);
+            buy_ids.push(fill.buy_id.clone());
+            sell_ids.push(fill.sell_id.clone());
+            timestamps.push(fill.timestamp);
+        }
+        (quantities, prices, buy_ids, sell_ids, timestamps)
+    }
+
+    /// Removes and returns every retained fill, in execution order, leaving
+    /// resting orders, the last trade and the event log untouched. Use it to
+    /// move fills into external storage so the history does not grow without
+    /// bound; methods that read the history, such as `trade_count` and
+    /// `fills_for_order`, only see fills produced after the last drain.
+    /// Unlike `clear`, the book itself is kept.
+    #[pyo3(text_signature = "($self)")]
+    pub fn drain_fills(&mut self) -> Vec<Fill> {
+        std::mem::take(&mut self.fills)
+    }
+
+    /// Returns every mutating operation applied to the book, in order.
+    #[pyo3(text_signature = "($self)")]
+    pub fn events(&self) -> Vec<Event> {
+        self.events.clone()
+    }
+
+    /// Rebuilds a book by re-applying `events` to an empty book with the given
+    /// settings. Adds are replayed with their recorded timestamps, so the
+    /// rebuilt book and its fills match the original run exactly.
+    ///
+    /// # Errors
+    /// - Returns an error if an event is rejected, e.g. a post-only add that
+    ///   would match or a modify with a non-positive quantity.
+    #[staticmethod]
+    #[pyo3(signature = (
+        events,
+        tick_size=0.01,
+        stp_policy=SelfTradePrevention::CancelNewest,
+        matching_mode=MatchingMode::Fifo,
+        fill_price_rule=FillPriceRule::Seller,
+        min_quantity=None,
+        max_quantity=None,
+        lot_size=None,
+        price_band_ticks=None,
+        level_priority=LevelPriority::Time,
+        quantity_is_integer=false,
+        maker_fee_bps=0.0,
+        taker_fee_bps=0.0,
+        max_orders=None,
+        crossed_book_policy=CrossedBookPolicy::Resolve,
+        tick_multiple=1,
+        allow_negative_prices=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn replay(
+        events: Vec<Event>,
+        tick_size: f64,
+        stp_policy: SelfTradePrevention,
+        matching_mode: MatchingMode,
+        fill_price_rule: FillPriceRule,
+        min_quantity: Option<f64>,
+        max_quantity: Option<f64>,
+        lot_size: Option<f64>,
+        price_band_ticks: Option<i64>,
+        level_priority: LevelPriority,
+        quantity_is_integer: bool,
+        maker_fee_bps: f64,
+        taker_fee_bps: f64,
+        max_orders: Option<usize>,
+        crossed_book_policy: CrossedBookPolicy,
+        tick_multiple: i64,
+        allow_negative_prices: bool,
+    ) -> PyResult<OrderBook> {
+        let mut book = Self::new(
+            tick_size,
+            stp_policy,
+            matching_mode,
+            fill_price_rule,
+            min_quantity,
+            max_quantity,
+            lot_size,
+            price_band_ticks,
+            level_priority,
+            quantity_is_integer,
+            maker_fee_bps,
+            taker_fee_bps,
+            max_orders,
+            crossed_book_policy,
+            tick_multiple,
+            allow_negative_prices,
+        )?;
+        for event in events {
+            match event {
+                Event::Add { order, timestamp } => {
+                    book.add_order(order, timestamp)?;
+                }
+                Event::Cancel { order_id } => {
+                    book.cancel(&order_id);
+                }
+                Event::Modify {
+                    order_id,
+                    new_quantity,
+                } => {
+                    book.modify_quantity(&order_id, new_quantity, false)?;
+                }
+                Event::Reduce { order_id, amount } => {
+                    book.reduce(&order_id, amount)?;
+                }
+                Event::RepricePegs { timestamp } => {
+                    book.reprice_pegs_at(timestamp)?;
+                }
+                Event::StartAuction {} => {
+                    book.start_auction();
+                }
+                Event::Uncross { timestamp } => {
+                    book.uncross_at(timestamp)?;
+                }
+            }
+        }
+        Ok(book)
+    }
+
+    /// Serializes the full state of the book to a JSON string.
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.snapshot()).map_err(|err| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Failed to serialize order book: {}",
+                err
+            ))
+        })
+    }
+
+    /// Restores a book from a JSON string produced by `to_json`.
+    #[staticmethod]
+    pub fn from_json(data: &str) -> PyResult<OrderBook> {
+        let snapshot: OrderBookSnapshot = serde_json::from_str(data).map_err(|err| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid order book JSON: {}", err))
+        })?;
+        Self::from_snapshot(snapshot)
+    }
+
+    /// Serializes the full state of the book to a compact binary snapshot.
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = bincode::serialize(&self.snapshot()).map_err(|err| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Failed to serialize order book: {}",
+                err
+            ))
+        })?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Restores a book from a binary snapshot produced by `to_bytes`.
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<OrderBook> {
+        let snapshot: OrderBookSnapshot = bincode::deserialize(data).map_err(|err| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid order book bytes: {}", err))
+        })?;
+        Self::from_snapshot(snapshot)
+    }
+
+    /// Audits the book's internal consistency, returning `true` if every
+    /// invariant holds:
+    /// - every price level is non-empty and holds open orders with positive
+    ///   visible quantity, on the matching side and at the level's price;
+    /// - every queued order and untriggered stop has an identical copy in the
+    ///   order lookup map, and no order ID is queued twice;
+    /// - every open order in the lookup map is either queued or a pending stop.
+    #[pyo3(text_signature = "($self)")]
+    pub fn validate(&self) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        let tracked = |order: &Order| {
+            self.orders
+                .get(&order.id)
+                .is_some_and(|tracked| tracked.same_state(order))
+        };
+
+        for (side, book) in [
+            (OrderType::Buy, &self.buy_orders),
+            (OrderType::Sell, &self.sell_orders),
+        ] {
+            for (price, queue) in book {
+                if queue.is_empty() {
+                    return false;
+                }
+                for order in queue {
+                    let consistent = order.side == side
+                        && order.price_in_ticks == *price
+                        && order.is_open()
+                        && order.quantity > 0.0
+                        && tracked(order)
+                        && seen.insert(order.id.as_str());
+                    if !consistent {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        for order in &self.stop_orders {
+            if !(order.is_open() && tracked(order) && seen.insert(order.id.as_str())) {
+                return false;
+            }
+        }
+
+        self.orders
+            .values()
+            .filter(|order| order.is_open())
+            .all(|order| seen.contains(order.id.as_str()))
+    }
+
+    /// Repairs the book's internal indexes, treating the price level queues
+    /// and the pending stops as the source of truth, and returns the number of
+    /// inconsistencies fixed: empty price levels are dropped, lookup entries
+    /// that differ from their queued or pending copy are replaced, and open
+    /// lookup entries that are neither queued nor pending are removed. Filled
+    /// and canceled entries are kept. This is a no-op on a healthy book, such
+    /// as one for which `validate` returns true, and takes O(n) time in the
+    /// number of tracked orders.
+    #[pyo3(text_signature = "($self)")]
+    pub fn compact(&mut self) -> usize {
+        let mut fixed = 0;
+        for book in [&mut self.buy_orders, &mut self.sell_orders] {
+            let levels = book.len();
+            book.retain(|_, queue| !queue.is_empty());
+            fixed += levels - book.len();
+        }
+
+        let mut live = HashSet::new();
+        let queued = self
+            .buy_orders
+            .values()
+            .chain(self.sell_orders.values())
+            .flatten()
+            .chain(&self.stop_orders);
+        for order in queued {
+            live.insert(order.id.clone());
+            if !self
+                .orders
+                .get(&order.id)
+                .is_some_and(|tracked| tracked.same_state(order))
+            {
+                self.orders.insert(order.id.clone(), order.clone());
+                fixed += 1;
+            }
+        }
+
+        let tracked = self.orders.len();
+        self.orders
+            .retain(|id, order| !order.is_open() || live.contains(id));
+        fixed + tracked - self.orders.len()
+    }
+
+    /// Returns the number of orders resting on the book. Filled orders and
+    /// untriggered stops are not counted.
+    fn __len__(&self) -> usize {
+        self.buy_orders
+            .values()
+            .chain(self.sell_orders.values())
+            .map(|queue| queue.len())
+            .sum()
+    }
+
+    /// Checks whether the order with `order_id` is resting on the book.
+    fn __contains__(&self, order_id: &str) -> bool {
+        self.resting_order(order_id).is_some()
+    }
+
+    /// Returns a string representation of the order book.
+    fn __repr__(&self) -> String {
+        let best_bid = self
+            .best_bid_level()
+            .map(|(price, qty)| format!("{:.2} @ {:.2}", qty, self.ticks_to_price(price)))
+            .unwrap_or_else(|| "None".to_string());
+
+        let best_ask = self
+            .best_ask_level()
+            .map(|(price, qty)| format!("{:.2} @ {:.2}", qty, self.ticks_to_price(price)))
+            .unwrap_or_else(|| "None".to_string());
+
+        let spread = match (self.best_bid_level(), self.best_ask_level()) {
+            (Some((bid, _)), Some((ask, _))) => {
+                format!("{:.4}", self.ticks_to_price(ask - bid))
+            }
+            _ => "None".to_string(),
+        };
+
+        format!(
+            "Best Bid: {}, Best Ask: {} (Spread: {})\nOpen Buy Volume: {:.2}, Open Sell Volume: {:.2}",
+            best_bid,
+            best_ask,
+            spread,
+            self.buy_volume(),
+            self.sell_volume()
+        )
+    }
+}
+
+impl OrderBook {
+    /// Helper method to convert a price to ticks for `create_order`, rounding
+    /// it onto the tick grid or, if `strict`, rejecting it when it is off-grid
+    fn round_price(
+        &self,
+        price: f64,
+        strict: bool,
+        rounding: &RoundingMode,
+        side: &OrderType,
+    ) -> PyResult<i64> {
+        let exact_ticks = self.tick.exact_ticks(price);
+        if strict && (exact_ticks - exact_ticks.round()).abs() > GRID_EPSILON {
+            return Err(OrderRejectReason::PriceOffTick.error(format!(
+                "Price {} is not a multiple of the tick size {}",
+                price,
+                self.tick_size()
+            )));
+        }
+
+        let price_in_ticks = rounding.round(exact_ticks, side);
+        if price_in_ticks <= 0 && !self.allow_negative_prices {
+            return Err(OrderRejectReason::NonPositivePrice
+                .error("Resulting price_in_ticks must be positive"));
+        }
+        Ok(price_in_ticks)
+    }
+
+    /// Helper method to check a quantity against the book's size limits
+    fn check_quantity(&self, quantity: f64) -> PyResult<()> {
+        self.check_whole(quantity)?;
+        if let Some(min_quantity) = self.min_quantity {
+            if quantity < min_quantity {
+                return Err(OrderRejectReason::BelowMinSize.error(format!(
+                    "Quantity {} is below the minimum order size {}",
+                    quantity, min_quantity
+                )));
+            }
+        }
+        if let Some(max_quantity) = self.max_quantity {
+            if quantity > max_quantity {
+                return Err(OrderRejectReason::AboveMaxSize.error(format!(
+                    "Quantity {} is above the maximum order size {}",
+                    quantity, max_quantity
+                )));
+            }
+        }
+        if let Some(lot_size) = self.lot_size {
+            let lots = quantity / lot_size;
+            if (lots - lots.round()).abs() > GRID_EPSILON {
+                return Err(OrderRejectReason::OffLotSize.error(format!(
+                    "Quantity {} is not a multiple of the lot size {}",
+                    quantity, lot_size
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper method to check that a quantity is whole in an integer book
+    fn check_whole(&self, quantity: f64) -> PyResult<()> {
+        if self.quantity_is_integer && quantity.fract() != 0.0 {
+            return Err(OrderRejectReason::FractionalQuantity
+                .error(format!("Quantity {} is not a whole number", quantity)));
+        }
+        Ok(())
+    }
+
+    /// Helper method to reject a limit order priced (or matching) at zero or
+    /// below in a book that does not allow negative prices
+    fn check_positive_price(&self, order: &Order) -> PyResult<()> {
+        if order.kind == OrderKind::Limit
+            && order.price_in_ticks.min(order.matching_ticks()) <= 0
+            && !self.allow_negative_prices
+        {
+            return Err(OrderRejectReason::NonPositivePrice.error(format!(
+                "Order {} has a non-positive price in a book without negative prices",
+                order.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper method to check a limit price against the book's price band.
+    /// Market orders and books that have not traded yet are never rejected.
+    fn check_price_band(&self, order: &Order) -> PyResult<()> {
+        let (Some(band), Some(last)) = (self.price_band_ticks, self.last_trade_ticks()) else {
+            return Ok(());
+        };
+        if order.kind == OrderKind::Limit && (order.price_in_ticks - last).abs() > band {
+            return Err(
+                OrderRejectReason::OutsidePriceBand.error_as::<PriceBandError>(format!(
+                    "Price of {} ticks is more than {} ticks from the last trade at {} ticks",
+                    order.price_in_ticks, band, last
+                )),
+            );
+        }
+        Ok(())
+    }
+
+    /// Helper method to check a limit price against the book's tick multiple.
+    /// Market orders are never rejected.
+    fn check_tick_multiple(&self, order: &Order) -> PyResult<()> {
+        if order.kind == OrderKind::Limit && order.price_in_ticks % self.tick_multiple != 0 {
+            return Err(OrderRejectReason::PriceOffTick.error(format!(
+                "Price of {} ticks is not a multiple of {} ticks",
+                order.price_in_ticks, self.tick_multiple
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper method to sum price × visible quantity across one side of the book
+    fn notional(&self, book: &BTreeMap<i64, VecDeque<Order>>) -> f64 {
+        book.iter()
+            .map(|(price, queue)| self.ticks_to_price(*price) * level_quantity(queue))
+            .sum()
+    }
+
+    /// Helper method to create an empty book with the same configuration
+    fn empty_like(&self) -> Self {
+        Self::new(
+            self.tick_size(),
+            self.stp_policy.clone(),
+            self.matching_mode.clone(),
+            self.fill_price_rule.clone(),
+            self.min_quantity,
+            self.max_quantity,
+            self.lot_size,
+            self.price_band_ticks,
+            self.level_priority.clone(),
+            self.quantity_is_integer,
+            self.maker_fee_bps,
+            self.taker_fee_bps,
+            self.max_orders,
+            self.crossed_book_policy.clone(),
+            self.tick_multiple,
+            self.allow_negative_prices,
+        )
+        .expect("Configuration was validated when the book was created")
+    }
+
+    /// Helper method to copy the live state of the book (its resting orders,
+    /// untriggered stops, positions and last trade) without the fill history
+    /// or event log, so dry runs cost the same however long the book has run.
+    /// Filled and canceled orders are not tracked by the copy.
+    fn scratch_copy(&self) -> Self {
+        let mut scratch = self.empty_like();
+        scratch.buy_orders = self.buy_orders.clone();
+        scratch.sell_orders = self.sell_orders.clone();
+        scratch.stop_orders = self.stop_orders.clone();
+        scratch.orders = scratch
+            .buy_orders
+            .values()
+            .chain(scratch.sell_orders.values())
+            .flatten()
+            .chain(&scratch.stop_orders)
+            .map(|order| (order.id.clone(), order.clone()))
+            .collect();
+        scratch.last_trade = self.last_trade.clone();
+        scratch.in_auction = self.in_auction;
+        scratch.positions = self.positions.clone();
+        scratch.seq = self.seq;
+        scratch
+    }
+
+    /// Helper method to capture the persistent state of the book
+    fn snapshot(&self) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            tick_size: self.tick_size(),
+            stp_policy: self.stp_policy.clone(),
+            matching_mode: self.matching_mode.clone(),
+            fill_price_rule: self.fill_price_rule.clone(),
+            min_quantity: self.min_quantity,
+            max_quantity: self.max_quantity,
+            lot_size: self.lot_size,
+            price_band_ticks: self.price_band_ticks,
+            level_priority: self.level_priority.clone(),
+            quantity_is_integer: self.quantity_is_integer,
+            maker_fee_bps: self.maker_fee_bps,
+            taker_fee_bps: self.taker_fee_bps,
+            max_orders: self.max_orders,
+            crossed_book_policy: self.crossed_book_policy.clone(),
+            tick_multiple: self.tick_multiple,
+            allow_negative_prices: self.allow_negative_prices,
+            buy_orders: self.buy_orders.clone(),
+            sell_orders: self.sell_orders.clone(),
+            orders: self
+                .orders
+                .iter()
+                .map(|(id, order)| (id.clone(), order.clone()))
+                .collect(),
+            stop_orders: self.stop_orders.clone(),
+            last_trade: self.last_trade.clone(),
+            fills: self.fills.clone(),
+            in_auction: self.in_auction,
+            positions: self
+                .positions
+                .iter()
+                .map(|(owner, position)| (owner.clone(), *position))
+                .collect(),
+            seq: self.seq,
+        }
+    }
+
+    /// Helper method to rebuild a book from a snapshot
+    fn from_snapshot(snapshot: OrderBookSnapshot) -> PyResult<Self> {
+        let mut book = Self::new(
+            snapshot.tick_size,
+            snapshot.stp_policy,
+            snapshot.matching_mode,
+            snapshot.fill_price_rule,
+            snapshot.min_quantity,
+            snapshot.max_quantity,
+            snapshot.lot_size,
+            snapshot.price_band_ticks,
+            snapshot.level_priority,
+            snapshot.quantity_is_integer,
+            snapshot.maker_fee_bps,
+            snapshot.taker_fee_bps,
+            snapshot.max_orders,
+            snapshot.crossed_book_policy,
+            snapshot.tick_multiple,
+            snapshot.allow_negative_prices,
+        )?;
+        book.buy_orders = snapshot.buy_orders;
+        book.sell_orders = snapshot.sell_orders;
+        book.orders = snapshot.orders.into_iter().collect();
+        book.stop_orders = snapshot.stop_orders;
+        book.last_trade = snapshot.last_trade;
+        book.fills = snapshot.fills;
+        book.in_auction = snapshot.in_auction;
+        book.positions = snapshot.positions.into_iter().collect();
+        book.seq = snapshot.seq;
+        let open_orders: Vec<Order> = book
+            .orders
+            .values()
+            .filter(|order| order.is_open())
+            .cloned()
+            .collect();
+        for order in &open_orders {
+            book.track_expiry(order);
+        }
+        Ok(book)
+    }
+
+    /// Helper method to get the resting orders on one side of the book
+    fn book_side(&self, side: &OrderType) -> &BTreeMap<i64, VecDeque<Order>> {
+        match side {
+            OrderType::Buy => &self.buy_orders,
+            OrderType::Sell => &self.sell_orders,
+        }
+    }
+
+    /// Helper method to get the resting orders on one side of the book
+    fn book_side_mut(&mut self, side: &OrderType) -> &mut BTreeMap<i64, VecDeque<Order>> {
+        match side {
+            OrderType::Buy => &mut self.buy_orders,
+            OrderType::Sell => &mut self.sell_orders,
+        }
+    }
+
+    /// Helper method to get an order only if it is resting in its price level
+    fn resting_order(&self, order_id: &str) -> Option<Order> {
+        let order = self.orders.get(order_id)?;
+        let book = match order.side {
+            OrderType::Buy => &self.buy_orders,
+            OrderType::Sell => &self.sell_orders,
+        };
+        book.get(&order.price_in_ticks)?
+            .iter()
+            .find(|o| o.id == order_id)
+            .cloned()
+    }
+
+    /// Helper method to get the price of the most recent fill in ticks
+    fn last_trade_ticks(&self) -> Option<i64> {
+        self.last_trade
+            .as_ref()
+            .map(|fill| self.price_to_ticks(fill.price))
+    }
+
+    /// Helper method to get best bid as `(price_in_ticks, quantity)`
+    fn best_bid_level(&self) -> Option<(i64, f64)> {
+        self.buy_orders
+            .iter()
+            .next_back()
+            .map(|(price, queue)| (*price, level_quantity(queue)))
+    }
+
+    /// Helper method to get best ask as `(price_in_ticks, quantity)`
+    fn best_ask_level(&self) -> Option<(i64, f64)> {
+        self.sell_orders
+            .iter()
+            .next()
+            .map(|(price, queue)| (*price, level_quantity(queue)))
+    }
+
+    /// Shared implementation of `add` and its variants. Fills are stamped with
+    /// `timestamp` if given, otherwise with the wall-clock time at submission,
+    /// which is recorded in the event log so the add can be replayed exactly.
+    /// The fill callback runs once the book is no longer borrowed, so it can
+    /// read the book.
+    fn submit(
+        slf: &Bound<'_, Self>,
+        incoming_order: Order,
+        timestamp: Option<u64>,
+    ) -> PyResult<Vec<Fill>> {
+        let timestamp = timestamp.unwrap_or_else(now_nanos);
+        let incoming_order = slf.borrow().vet_order(incoming_order, timestamp)?;
+        Self::check_order_filter(slf, &incoming_order)?;
+        let fills = slf.borrow_mut().apply_order(incoming_order, timestamp)?;
+        Self::notify_fills(slf, &fills)?;
+        Ok(fills)
+    }
+
+    /// Shared implementation of `submit` and its replay, which applies an add
+    /// without invoking the order filter or the fill callback.
+    fn add_order(&mut self, incoming_order: Order, timestamp: u64) -> PyResult<Vec<Fill>> {
+        let incoming_order = self.vet_order(incoming_order, timestamp)?;
+        self.apply_order(incoming_order, timestamp)
+    }
+
+    /// Helper method to reject an order ID that the book already tracks
+    fn check_new_id(&self, order_id: &str) -> PyResult<()> {
+        if self.orders.contains_key(order_id) {
+            return Err(OrderRejectReason::DuplicateOrderId.error(format!(
+                "Order {} has already been added to the book",
+                order_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper method to run the book's own checks on an incoming order,
+    /// returning it as it would be applied: pegged orders priced and
+    /// reduce-only orders trimmed. The book is not modified.
+    fn vet_order(&self, mut incoming_order: Order, timestamp: u64) -> PyResult<Order> {
+        self.check_new_id(&incoming_order.id)?;
+        self.check_quantity(incoming_order.quantity + incoming_order.hidden_quantity)?;
+        self.check_whole(incoming_order.quantity)?;
+        if self.in_auction
+            && (incoming_order.kind == OrderKind::Market
+                || incoming_order.time_in_force != TimeInForce::GTC)
+        {
+            return Err(OrderRejectReason::NotAllowedInAuction
+                .error("Only GTC limit orders can be added during an auction"));
+        }
+        self.price_peg(&mut incoming_order)?;
+        self.check_positive_price(&incoming_order)?;
+        self.check_price_band(&incoming_order)?;
+        self.check_tick_multiple(&incoming_order)?;
+        self.cap_reduce_only(&mut incoming_order)?;
+        if !self.in_auction && incoming_order.stop_triggered(self.last_trade_ticks()) {
+            self.check_post_only(&incoming_order)?;
+        }
+        if self.crossed_book_policy == CrossedBookPolicy::Reject
+            && !self.in_auction
+            && self.may_cross(&incoming_order)
+            && self.would_cross(&incoming_order, timestamp)?
+        {
+            return Err(OrderRejectReason::CrossedBook.error(format!(
+                "Order {} would leave the book locked or crossed",
+                incoming_order.id
+            )));
+        }
+        Ok(incoming_order)
+    }
+
+    /// Helper method to apply an order that has passed `vet_order`, matching
+    /// it and logging the add.
+    fn apply_order(&mut self, incoming_order: Order, timestamp: u64) -> PyResult<Vec<Fill>> {
+        self.self_trade_canceled.clear();
+        self.evicted.clear();
+        let event = Event::Add {
+            order: incoming_order.clone(),
+            timestamp,
+        };
+        self.stats.orders_added += 1;
+
+        if !incoming_order.stop_triggered(self.last_trade_ticks()) {
+            self.update_order(&incoming_order);
+            self.track_expiry(&incoming_order);
+            self.stop_orders.push(incoming_order);
+            self.events.push(event);
+            return Ok(Vec::new());
+        }
+
+        if self.in_auction {
+            self.update_order(&incoming_order);
+            self.track_expiry(&incoming_order);
+            self.book_side_mut(&incoming_order.side)
+                .entry(incoming_order.price_in_ticks)
+                .or_default()
+                .push_back(incoming_order);
+            self.evict_excess();
+            self.events.push(event);
+            return Ok(Vec::new());
+        }
+
+        let was_empty = self.is_empty_book();
+        let best_bid = self.best_bid_level().map(|(price, _)| price);
+        let expiry = incoming_order
+            .expires_at
+            .map(|expires_at| (expires_at, incoming_order.id.clone()));
+        let mut fills = self.match_order(incoming_order, Some(timestamp))?;
+        self.trigger_stops(&mut fills, Some(timestamp))?;
+        self.resolve_cross(best_bid, &mut fills, Some(timestamp))?;
+        if let Some((expires_at, order_id)) = expiry {
+            if self
+                .orders
+                .get(&order_id)
+                .is_some_and(|order| order.is_open())
+            {
+                self.expiries.push(Reverse((expires_at, order_id)));
+            }
+        }
+        self.record_fills(&fills);
+        self.evict_excess();
+        self.track_emptied(was_empty);
+        self.events.push(event);
+        self.assert_not_crossed();
+        Ok(fills)
+    }
+
+    /// Helper method to put a removed resting order back at `index` in its
+    /// price level
+    fn restore_order(&mut self, order: Order, index: usize) {
+        self.update_order(&order);
+        let queue = self
+            .book_side_mut(&order.side)
+            .entry(order.price_in_ticks)
+            .or_default();
+        queue.insert(index.min(queue.len()), order);
+    }
+
+    /// Helper method to remove an order from the book and from tracking,
+    /// returning it marked `Canceled`, without logging an event.
+    fn remove_order(&mut self, order_id: &str) -> Option<Order> {
+        // Use a scoped block to avoid overlapping mutable borrows
+        let mut canceled_order = None;
+
+        if let Some(order) = self.orders.get_mut(order_id) {
+            // Mark the order as canceled
+            order.status = OrderStatus::Canceled;
+
+            // Determine which book to remove it from
+            let target_book = match order.side {
+                OrderType::Buy => &mut self.buy_orders,
+                OrderType::Sell => &mut self.sell_orders,
+            };
+
+            // Find the specific price level queue
+            if let Some(queue) = target_book.get_mut(&order.price_in_ticks) {
+                // Remove the order from the queue
+                queue.retain(|o| o.id != order.id);
+
+                // Remove the price level if the queue is empty
+                if queue.is_empty() {
+                    target_book.remove(&order.price_in_ticks);
+                }
+            }
+
+            // Take ownership of the modified order for updating outside the borrow
+            canceled_order = Some(order.clone());
+        }
+
+        if let Some(order) = &canceled_order {
+            self.orders.remove(&order.id);
+            self.stop_orders.retain(|o| o.id != order.id);
+        }
+
+        canceled_order
+    }
+
+    /// Helper method to evict resting orders while the book holds more than
+    /// `max_orders`. Each victim is the oldest order at the least aggressive
+    /// price on either side (the lowest bid or the highest ask), so liquidity
+    /// near the touch is kept.
+    fn evict_excess(&mut self) {
+        let Some(max_orders) = self.max_orders else {
+            return;
+        };
+        while self.__len__() > max_orders {
+            let victim = [
+                self.buy_orders.values().next(),
+                self.sell_orders.values().next_back(),
+            ]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .min_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)))
+            .map(|order| order.id.clone());
+            let Some(order) = victim.and_then(|id| self.remove_order(&id)) else {
+                break;
+            };
+            self.evicted.push(order);
+        }
+    }
+
+    /// Helper method to append new fills to the book's history and apply them
+    /// to their owners' positions.
+    fn record_fills(&mut self, fills: &[Fill]) {
+        for fill in fills {
+            for (order_id, signed) in [
+                (&fill.buy_id, fill.quantity),
+                (&fill.sell_id, -fill.quantity),
+            ] {
+                let owner = self
+                    .orders
+                    .get(order_id)
+                    .and_then(|order| order.owner.clone());
+                if let Some(owner) = owner {
+                    *self.positions.entry(owner).or_default() += signed;
+                }
+            }
+        }
+        self.stats.fills += fills.len() as u64;
+        self.stats.volume += fills.iter().map(|fill| fill.quantity).sum::<f64>();
+        self.fills.extend(fills.iter().cloned());
+    }
+
+    /// Helper method to schedule a good-till-date order for `expire`. Entries
+    /// are never removed when an order fills or is canceled; `expire` skips
+    /// them instead.
+    fn track_expiry(&mut self, order: &Order) {
+        if let Some(expires_at) = order.expires_at {
+            self.expiries.push(Reverse((expires_at, order.id.clone())));
+        }
+    }
+
+    /// Helper method to check whether no orders rest on either side
+    fn is_empty_book(&self) -> bool {
+        self.buy_orders.is_empty() && self.sell_orders.is_empty()
+    }
+
+    /// Helper method to count an operation that left a previously non-empty
+    /// book with no resting orders
+    fn track_emptied(&mut self, was_empty: bool) {
+        if !was_empty && self.is_empty_book() {
+            self.stats.times_emptied += 1;
+        }
+    }
+
+    /// Helper method to trim a reduce-only order to the position its owner
+    /// can still reduce: the owner's net filled quantity on the opposite side,
+    /// less any of the owner's reduce-only orders already working on the same
+    /// side. Hidden iceberg quantity is trimmed before the displayed slice.
+    fn cap_reduce_only(&self, order: &mut Order) -> PyResult<()> {
+        if !order.reduce_only {
+            return Ok(());
+        }
+        let position = order
+            .owner
+            .as_ref()
+            .and_then(|owner| self.positions.get(owner))
+            .copied()
+            .unwrap_or(0.0);
+        let reducible = match order.side {
+            OrderType::Buy => (-position).max(0.0),
+            OrderType::Sell => position.max(0.0),
+        };
+        let working: f64 = self
+            .orders
+            .values()
+            .filter(|o| {
+                o.reduce_only && o.is_open() && o.side == order.side && o.owner == order.owner
+            })
+            .map(|o| o.quantity + o.hidden_quantity)
+            .sum();
+        let allowed = reducible - working;
+        if allowed <= QUANTITY_EPSILON {
+            return Err(OrderRejectReason::ReduceOnlyWouldIncrease
+                .error("Reduce-only order has no position left to reduce"));
+        }
+
+        let excess = order.quantity + order.hidden_quantity - allowed;
+        if excess > 0.0 {
+            let hidden_cut = excess.min(order.hidden_quantity);
+            order.hidden_quantity -= hidden_cut;
+            order.quantity -= excess - hidden_cut;
+        }
+        Ok(())
+    }
+
+    /// Shared implementation of `reprice_pegs` and its replay.
+    fn reprice_pegs_at(&mut self, timestamp: u64) -> PyResult<Vec<Fill>> {
+        let mut pegs: Vec<(u64, String)> = self
+            .buy_orders
+            .values()
+            .chain(self.sell_orders.values())
+            .flatten()
+            .filter(|order| order.peg_reference.is_some())
+            .map(|order| (order.timestamp, order.id.clone()))
+            .collect();
+        pegs.sort();
+
+        let mut fills = Vec::new();
+        for (_, order_id) in pegs {
+            // An earlier repriced peg may have traded this one away
+            let Some(mut order) = self.resting_order(&order_id) else {
+                continue;
+            };
+            let Some(price_in_ticks) = self.peg_price_ticks(&order) else {
+                continue;
+            };
+            if price_in_ticks == order.price_in_ticks {
+                continue;
+            }
 
-                        if buy_queue.is_empty() {
-                            self.buy_orders.remove(&best_buy_price);
-                        }
+            // A new price that `add` would reject leaves the peg where it is
+            let mut repriced = order.clone();
+            repriced.price_in_ticks = price_in_ticks;
+            repriced.timestamp = timestamp;
+            if self.check_price_band(&repriced).is_err()
+                || self.check_tick_multiple(&repriced).is_err()
+            {
+                continue;
+            }
+
+            let side = self.book_side_mut(&order.side);
+            let queue = side
+                .get_mut(&order.price_in_ticks)
+                .expect("Order is resting on the book");
+            let index = queue
+                .iter()
+                .position(|o| o.id == order.id)
+                .expect("Order is resting on the book");
+            order = queue
+                .remove(index)
+                .expect("Index is within the price level");
+            if queue.is_empty() {
+                side.remove(&order.price_in_ticks);
+            }
 
-                        (best_buy_price, resting_buy)
-                    };
+            if self.in_auction {
+                self.update_order(&repriced);
+                self.book_side_mut(&repriced.side)
+                    .entry(repriced.price_in_ticks)
+                    .or_default()
+                    .push_back(repriced);
+            } else if self.crossed_book_policy == CrossedBookPolicy::Reject
+                && self.may_cross(&repriced)
+                && self.would_cross(&repriced, timestamp)?
+            {
+                self.restore_order(order, index);
+            } else {
+                let best_bid = self.best_bid_level().map(|(price, _)| price);
+                fills.extend(self.match_order(repriced, Some(timestamp))?);
+                self.resolve_cross(best_bid, &mut fills, Some(timestamp))?;
+            }
+        }
 
-                    // Update the resting buy order and incoming order in the `orders` map
-                    self.update_order(&resting_buy);
-                    self.update_order(&incoming_order);
-                }
+        self.trigger_stops(&mut fills, Some(timestamp))?;
+        self.record_fills(&fills);
+        self.events.push(Event::RepricePegs { timestamp });
+        self.assert_not_crossed();
+        Ok(fills)
+    }
+
+    /// Shared implementation of `uncross` and its replay.
+    fn uncross_at(&mut self, timestamp: u64) -> PyResult<(Option<f64>, Vec<Fill>)> {
+        self.in_auction = false;
+        self.self_trade_canceled.clear();
+        let clearing_ticks = self.clearing_price_ticks();
+
+        let mut fills = Vec::new();
+        while let Some(price) = clearing_ticks {
+            let (Some((bid, buy_index)), Some((ask, sell_index))) = (
+                self.auction_front(&OrderType::Buy, price),
+                self.auction_front(&OrderType::Sell, price),
+            ) else {
+                break;
+            };
+            let mut buy = self.take_at(&OrderType::Buy, bid, buy_index);
+            let mut sell = self.take_at(&OrderType::Sell, ask, sell_index);
+            let (resting, incoming) = if buy.timestamp <= sell.timestamp {
+                (&mut buy, &mut sell)
+            } else {
+                (&mut sell, &mut buy)
+            };
+            if let Some(mut fill) = resting.match_against(
+                incoming,
+                &self.stp_policy,
+                &FillPriceRule::Maker,
+                self.tick,
+                Some(timestamp),
+            ) {
+                fill.price = self.ticks_to_price(price);
+                fills.push(fill);
+            }
+            self.requeue(buy, buy_index);
+            self.requeue(sell, sell_index);
+        }
+
+        self.sequence_fills(&mut fills);
+        self.charge_fees(&mut fills);
+        if let Some(fill) = fills.last() {
+            self.last_trade = Some(fill.clone());
+        }
+        self.trigger_stops(&mut fills, Some(timestamp))?;
+        self.record_fills(&fills);
+        self.events.push(Event::Uncross { timestamp });
+        Ok((
+            clearing_ticks.map(|ticks| self.ticks_to_price(ticks)),
+            fills,
+        ))
+    }
+
+    /// Helper method to number new fills in execution order
+    fn sequence_fills(&mut self, fills: &mut [Fill]) {
+        for fill in fills {
+            self.seq += 1;
+            fill.sequence = self.seq;
+        }
+    }
 
-                if incoming_order.is_open() {
-                    let price_ticks = incoming_order.price_in_ticks;
-                    self.sell_orders
-                        .entry(price_ticks)
-                        .or_default()
-                        .push_back(incoming_order.clone());
+    /// Helper method to charge the book's maker and taker fees on new fills,
+    /// in basis points of each fill's notional
+    fn charge_fees(&self, fills: &mut [Fill]) {
+        for fill in fills {
+            let notional = fill.quantity * fill.price;
+            fill.maker_fee = notional * self.maker_fee_bps / 10_000.0;
+            fill.taker_fee = notional * self.taker_fee_bps / 10_000.0;
+        }
+    }
+
+    /// Helper method to find the auction clearing price in ticks, following
+    /// the tie-breaking rules described on `uncross`. Orders are counted at
+    /// their matching prices, which is what they trade on.
+    fn clearing_price_ticks(&self) -> Option<i64> {
+        let executable = |order: &Order| order.quantity + order.hidden_quantity;
+        let resting = |book: &BTreeMap<i64, VecDeque<Order>>| -> Vec<(i64, f64)> {
+            book.values()
+                .flatten()
+                .map(|order| (order.matching_ticks(), executable(order)))
+                .collect()
+        };
+        let (bids, asks) = (resting(&self.buy_orders), resting(&self.sell_orders));
+        let prices: BTreeSet<i64> = bids.iter().chain(&asks).map(|&(price, _)| price).collect();
+        let distance = |price: i64| {
+            self.last_trade_ticks()
+                .map_or(0, |last| (price - last).abs())
+        };
+
+        let mut best: Option<(i64, f64, f64)> = None; // (price, volume, imbalance)
+        for price in prices {
+            let demand: f64 = bids
+                .iter()
+                .filter(|&&(bid, _)| bid >= price)
+                .map(|&(_, quantity)| quantity)
+                .sum();
+            let supply: f64 = asks
+                .iter()
+                .filter(|&&(ask, _)| ask <= price)
+                .map(|&(_, quantity)| quantity)
+                .sum();
+            let volume = demand.min(supply);
+            if volume <= 0.0 {
+                continue;
+            }
+            let imbalance = (demand - supply).abs();
+            let better = match best {
+                None => true,
+                Some((best_price, best_volume, best_imbalance)) => {
+                    volume > best_volume
+                        || (volume == best_volume && imbalance < best_imbalance)
+                        || (volume == best_volume
+                            && imbalance == best_imbalance
+                            && distance(price) < distance(best_price))
                 }
+            };
+            if better {
+                best = Some((price, volume, imbalance));
             }
         }
+        best.map(|(price, _, _)| price)
+    }
 
-        // Always ensure the incoming order is updated in `orders` at the end
-        self.update_order(&incoming_order);
+    /// Helper method to find the first order on `side`, in price-time
+    /// priority, willing to trade at the clearing price. Returns its price
+    /// level and its index in that level.
+    fn auction_front(&self, side: &OrderType, clearing_ticks: i64) -> Option<(i64, usize)> {
+        let first = |(&price, queue): (&i64, &VecDeque<Order>)| {
+            queue
+                .iter()
+                .position(|order| order.accepts_price(clearing_ticks))
+                .map(|index| (price, index))
+        };
+        match side {
+            OrderType::Buy => self.buy_orders.iter().rev().find_map(first),
+            OrderType::Sell => self.sell_orders.iter().find_map(first),
+        }
+    }
 
-        Ok(fills)
+    /// Helper method to take the order at `index` in a price level, dropping
+    /// the level if it empties
+    fn take_at(&mut self, side: &OrderType, price_in_ticks: i64, index: usize) -> Order {
+        let book = self.book_side_mut(side);
+        let queue = book
+            .get_mut(&price_in_ticks)
+            .expect("Price level exists in the book");
+        let order = queue
+            .remove(index)
+            .expect("Index is within the price level");
+        if queue.is_empty() {
+            book.remove(&price_in_ticks);
+        }
+        order
     }
 
-    /// Cancels an order by its ID.
-    #[pyo3(text_signature = "(self, order_id)")]
-    pub fn cancel(&mut self, order_id: &str) -> bool {
-        // Use a scoped block to avoid overlapping mutable borrows
-        let mut canceled_order = None;
+    /// Helper method to return an order taken with `take_at` to the book
+    /// after a match. It keeps its place at `index` in its level unless its
+    /// iceberg slice was refreshed, and leaves the book once filled or
+    /// canceled by self-trade prevention.
+    fn requeue(&mut self, mut order: Order, index: usize) {
+        if order.status == OrderStatus::Canceled {
+            self.orders.remove(&order.id);
+            self.self_trade_canceled.push(order);
+            return;
+        }
+        let replenished = order.replenish();
+        if !order.is_open() {
+            self.update_order(&order);
+        } else if replenished {
+            self.update_order(&order);
+            self.book_side_mut(&order.side)
+                .entry(order.price_in_ticks)
+                .or_default()
+                .push_back(order);
+        } else {
+            self.restore_order(order, index);
+        }
+    }
 
-        if let Some(order) = self.orders.get_mut(order_id) {
-            // Mark the order as canceled
-            order.status = OrderStatus::Canceled;
+    /// Helper method to check whether an order reaches the opposite best
+    /// price, on either its display or its matching price, so adding it could
+    /// trade or leave the book locked or crossed.
+    fn may_cross(&self, order: &Order) -> bool {
+        let touch = match order.side {
+            OrderType::Buy => self.best_ask_level(),
+            OrderType::Sell => self.best_bid_level(),
+        };
+        let Some((touch, _)) = touch else {
+            return false;
+        };
+        let reaches_display = match order.side {
+            OrderType::Buy => order.price_in_ticks >= touch,
+            OrderType::Sell => order.price_in_ticks <= touch,
+        };
+        reaches_display || order.accepts_price(touch)
+    }
 
-            // Determine which book to remove it from
-            let target_book = match order.side {
-                OrderType::Buy => &mut self.buy_orders,
-                OrderType::Sell => &mut self.sell_orders,
+    /// Helper method to check whether adding `order` would leave the book
+    /// locked or crossed, by matching it on a scratch copy of the book and
+    /// seeing whether `resolve_cross` had to step in.
+    fn would_cross(&self, order: &Order, timestamp: u64) -> PyResult<bool> {
+        if !order.stop_triggered(self.last_trade_ticks()) {
+            return Ok(false);
+        }
+        let mut scratch = self.scratch_copy();
+        let best_bid = scratch.best_bid_level().map(|(price, _)| price);
+        let mut fills = scratch.match_order(order.clone(), Some(timestamp))?;
+        scratch.trigger_stops(&mut fills, Some(timestamp))?;
+        scratch.resolve_cross(best_bid, &mut fills, Some(timestamp))?;
+        Ok(scratch.stats.crosses_resolved > 0)
+    }
+
+    /// Helper method to resolve a crossed book after an add. `best_bid` is the best
+    /// bid in ticks before the add: if the bid has moved above it the buy side
+    /// crossed, otherwise the sell side did. The newest order at that level is
+    /// re-run through matching and, if that produces no fills, canceled, until
+    /// the best bid is below the best ask again.
+    fn resolve_cross(
+        &mut self,
+        best_bid: Option<i64>,
+        fills: &mut Vec<Fill>,
+        timestamp: Option<u64>,
+    ) -> PyResult<()> {
+        let mut crossed = false;
+        while let (Some((bid, _)), Some((ask, _))) = (self.best_bid_level(), self.best_ask_level())
+        {
+            if bid < ask {
+                break;
+            }
+            crossed = true;
+            let (side, price) = match best_bid {
+                Some(old_bid) if bid <= old_bid => (OrderType::Sell, ask),
+                _ => (OrderType::Buy, bid),
             };
+            let book = self.book_side_mut(&side);
+            let queue = book
+                .get_mut(&price)
+                .expect("Price level exists in the book");
+            let order = queue.pop_back().expect("Price levels are never empty");
+            if queue.is_empty() {
+                book.remove(&price);
+            }
 
-            // Find the specific price level queue
-            if let Some(queue) = target_book.get_mut(&order.price_in_ticks) {
-                // Remove the order from the queue
-                queue.retain(|o| o.id != order.id);
+            let order_id = order.id.clone();
+            let rerun = self.match_order(order, timestamp)?;
+            if rerun.is_empty() && self.remove_order(&order_id).is_some() {
+                self.stats.orders_canceled += 1;
+            }
+            fills.extend(rerun);
+        }
+        if crossed {
+            self.stats.crosses_resolved += 1;
+        }
+        Ok(())
+    }
+
+    /// Helper method to catch matching bugs in debug builds. Outside an
+    /// auction, every add leaves the best bid below the best ask.
+    fn assert_not_crossed(&self) {
+        debug_assert!(
+            self.in_auction || !self.is_crossed(),
+            "Order book is crossed: bid {:?} is above ask {:?}",
+            self.best_bid_level(),
+            self.best_ask_level()
+        );
+    }
+
+    /// Helper method to pass each new fill to the fill callback, if any. The
+    /// book must not be borrowed, so the callback can read it.
+    fn notify_fills(slf: &Bound<'_, Self>, fills: &[Fill]) -> PyResult<()> {
+        let py = slf.py();
+        let Some(on_fill) = slf.borrow().on_fill.as_ref().map(|cb| cb.clone_ref(py)) else {
+            return Ok(());
+        };
+        fills
+            .iter()
+            .try_for_each(|fill| on_fill.call1(py, (fill.clone(),)).map(|_| ()))
+    }
+
+    /// Helper method to reject an order refused by the order filter, if any.
+    /// The book must not be borrowed, so the filter can read it.
+    fn check_order_filter(slf: &Bound<'_, Self>, order: &Order) -> PyResult<()> {
+        let py = slf.py();
+        let Some(order_filter) = slf.borrow().order_filter.as_ref().map(|f| f.clone_ref(py)) else {
+            return Ok(());
+        };
+        let accepted = order_filter
+            .call1(py, (order.clone(),))?
+            .bind(py)
+            .is_truthy()?;
+        if !accepted {
+            return Err(OrderRejectReason::FilteredOut.error(format!(
+                "Order {} was refused by the order filter",
+                order.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper method to set a pegged order's price from its reference
+    fn price_peg(&self, order: &mut Order) -> PyResult<()> {
+        if order.peg_reference.is_none() {
+            return Ok(());
+        }
+        order.price_in_ticks = self.peg_price_ticks(order).ok_or_else(|| {
+            OrderRejectReason::PegUnavailable.error("Peg reference price is unavailable")
+        })?;
+        Ok(())
+    }
+
+    /// Helper method to compute a pegged order's price in ticks, ignoring other
+    /// pegged orders. Returns None if the reference side is empty or the
+    /// offset would take the price to zero or below in a book that does not
+    /// allow negative prices.
+    fn peg_price_ticks(&self, order: &Order) -> Option<i64> {
+        let unpegged = |queue: &VecDeque<Order>| queue.iter().any(|o| o.peg_reference.is_none());
+        let best_bid = || {
+            self.buy_orders
+                .iter()
+                .rev()
+                .find(|(_, queue)| unpegged(queue))
+                .map(|(price, _)| *price)
+        };
+        let best_ask = || {
+            self.sell_orders
+                .iter()
+                .find(|(_, queue)| unpegged(queue))
+                .map(|(price, _)| *price)
+        };
+
+        let reference = match order.peg_reference.as_ref()? {
+            PegReference::BestBid => best_bid()?,
+            PegReference::BestAsk => best_ask()?,
+            PegReference::Mid => {
+                let sum = best_bid()? + best_ask()?;
+                match order.side {
+                    OrderType::Buy => sum.div_euclid(2),
+                    OrderType::Sell => (sum + 1).div_euclid(2),
+                }
+            }
+        };
+        let price_in_ticks = reference + order.peg_offset_ticks;
+        (price_in_ticks > 0 || self.allow_negative_prices).then_some(price_in_ticks)
+    }
+
+    /// Helper method to sum the resting quantity a `FOK` order could match,
+    /// judged on matching prices like the fill loop: levels are walked from
+    /// the touch while the order accepts their price, and only resting orders
+    /// whose matching price crosses are counted. The order's own resting
+    /// orders are never counted. Under `CancelOldest` they are skipped, since
+    /// they are canceled and matching goes on; every other policy cancels or
+    /// shrinks the `FOK` order at the first of them, so counting stops there.
+    fn fok_liquidity(&self, order: &Order) -> f64 {
+        let levels: Box<dyn Iterator<Item = (&i64, &VecDeque<Order>)>> = match order.side {
+            OrderType::Buy => Box::new(self.sell_orders.iter()),
+            OrderType::Sell => Box::new(self.buy_orders.iter().rev()),
+        };
+        let mut liquidity = 0.0;
+        for resting in levels
+            .take_while(|(price, _)| order.accepts_price(**price))
+            .flat_map(|(_, queue)| queue)
+        {
+            if order.self_trades_with(resting) {
+                if self.stp_policy == SelfTradePrevention::CancelOldest {
+                    continue;
+                }
+                break;
+            }
+            if resting.can_match(order) {
+                liquidity += resting.quantity + resting.hidden_quantity;
+            }
+        }
+        liquidity
+    }
+
+    /// Helper method to reject a post-only order that would take liquidity,
+    /// since it must rest
+    fn check_post_only(&self, order: &Order) -> PyResult<()> {
+        if !order.post_only {
+            return Ok(());
+        }
+        let would_cross = match order.side {
+            OrderType::Buy => self
+                .best_ask_level()
+                .is_some_and(|(ask, _)| order.price_in_ticks >= ask),
+            OrderType::Sell => self
+                .best_bid_level()
+                .is_some_and(|(bid, _)| order.price_in_ticks <= bid),
+        };
+        if would_cross {
+            return Err(OrderRejectReason::PostOnlyWouldMatch
+                .error("Post-only order would immediately match"));
+        }
+        Ok(())
+    }
+
+    /// Matches an incoming order against the book, resting or canceling any
+    /// remainder according to its time in force.
+    fn match_order(
+        &mut self,
+        mut incoming_order: Order,
+        timestamp: Option<u64>,
+    ) -> PyResult<Vec<Fill>> {
+        let mut fills = Vec::new();
+        self.check_post_only(&incoming_order)?;
+
+        // A FOK order that cannot be filled in full is killed before touching the book
+        if incoming_order.time_in_force == TimeInForce::FOK
+            && self.fok_liquidity(&incoming_order) < incoming_order.quantity
+        {
+            return Ok(fills);
+        }
+
+        let mut step = 0;
+        let mut min_fill_blocked = false;
+
+        // A collared order never trades beyond its slippage from the opposite
+        // touch as it stood before the order started matching
+        let collar_ticks =
+            incoming_order
+                .max_slippage_ticks
+                .and_then(|slippage| match incoming_order.side {
+                    OrderType::Buy => self.best_ask_level().map(|(ask, _)| ask + slippage),
+                    OrderType::Sell => self.best_bid_level().map(|(bid, _)| bid - slippage),
+                });
+        while incoming_order.is_open() {
+            step += 1;
+            let touched = {
+                // Restrict the mutable borrow of the opposite book to this block
+                let opposite_book = match incoming_order.side {
+                    OrderType::Buy => &mut self.sell_orders,
+                    OrderType::Sell => &mut self.buy_orders,
+                };
+                let best_level = match incoming_order.side {
+                    OrderType::Buy => opposite_book.iter_mut().next(),
+                    OrderType::Sell => opposite_book.iter_mut().next_back(),
+                };
+                let (best_price, queue) = match best_level {
+                    Some((k, q)) => (*k, q),
+                    None => break,
+                };
+
+                let beyond_collar = collar_ticks.is_some_and(|collar| match incoming_order.side {
+                    OrderType::Buy => best_price > collar,
+                    OrderType::Sell => best_price < collar,
+                });
+                if !incoming_order.accepts_price(best_price) || beyond_collar {
+                    break;
+                }
+
+                let threshold = incoming_order.min_fill_threshold();
+                let touched = match self.matching_mode {
+                    MatchingMode::Fifo => {
+                        let index = self.level_priority.select(queue, &incoming_order, step);
+                        // Levels are walked by display price, so stop at a
+                        // resting order whose matching price does not cross
+                        let resting = &queue[index];
+                        if !incoming_order.self_trades_with(resting)
+                            && !resting.can_match(&incoming_order)
+                        {
+                            break;
+                        }
+                        if let Some(threshold) = threshold {
+                            let resting = &queue[index];
+                            let execution = resting.quantity.min(incoming_order.quantity);
+                            if !incoming_order.self_trades_with(resting)
+                                && execution < threshold - QUANTITY_EPSILON
+                            {
+                                min_fill_blocked = true;
+                                break;
+                            }
+                        }
+                        let resting_order = &mut queue[index];
+                        if let Some(fill) = resting_order.match_against(
+                            &mut incoming_order,
+                            &self.stp_policy,
+                            &self.fill_price_rule,
+                            self.tick,
+                            timestamp,
+                        ) {
+                            fills.push(fill);
+                        }
+                        incoming_order.replenish();
+
+                        // A partially filled resting order is updated in place and
+                        // keeps its place in the queue, a refreshed iceberg slice
+                        // loses time priority, and an exhausted order is removed
+                        if resting_order.replenish() {
+                            let resting_order =
+                                queue.remove(index).expect("Index is within the queue");
+                            sync_fill_state(&mut self.orders, &resting_order);
+                            queue.push_back(resting_order);
+                            Vec::new()
+                        } else if resting_order.is_open() {
+                            sync_fill_state(&mut self.orders, resting_order);
+                            Vec::new()
+                        } else {
+                            vec![queue.remove(index).expect("Index is within the queue")]
+                        }
+                    }
+                    MatchingMode::ProRata => {
+                        // Stop at a level where no matching price crosses
+                        if !queue.iter().any(|order| {
+                            incoming_order.self_trades_with(order)
+                                || order.can_match(&incoming_order)
+                        }) {
+                            break;
+                        }
+                        if let Some(threshold) = threshold {
+                            let available: f64 = queue
+                                .iter()
+                                .filter(|order| {
+                                    !incoming_order.self_trades_with(order)
+                                        && order.can_match(&incoming_order)
+                                })
+                                .map(|order| order.quantity)
+                                .sum();
+                            if available.min(incoming_order.quantity) < threshold - QUANTITY_EPSILON
+                            {
+                                min_fill_blocked = true;
+                                break;
+                            }
+                        }
+                        let mut level: Vec<Order> = queue.drain(..).collect();
+
+                        // Self-trades are resolved first and take no part in the allocation
+                        for resting_order in level.iter_mut() {
+                            if incoming_order.is_open()
+                                && incoming_order.self_trades_with(resting_order)
+                            {
+                                self.stp_policy.apply(resting_order, &mut incoming_order);
+                            }
+                        }
+
+                        let eligible = |order: &Order| {
+                            order.is_open()
+                                && !incoming_order.self_trades_with(order)
+                                && order.can_match(&incoming_order)
+                        };
+                        let eligible_count = level.iter().filter(|order| eligible(order)).count();
+                        let eligible_quantity: f64 = level
+                            .iter()
+                            .filter(|order| eligible(order))
+                            .map(|order| order.quantity)
+                            .sum();
+                        let traded = if incoming_order.is_open() {
+                            incoming_order.quantity.min(eligible_quantity)
+                        } else {
+                            0.0
+                        };
+
+                        // Integer books round shares down, leaving any odd lots to
+                        // the last order or a further pass over the level
+                        let whole = self.quantity_is_integer;
+                        let mut remaining = traded;
+                        let mut remaining_orders = eligible_count;
+                        let allocations: Vec<f64> = level
+                            .iter()
+                            .map(|order| {
+                                if !eligible(order) {
+                                    return 0.0;
+                                }
+                                remaining_orders -= 1;
+                                let share = if remaining_orders == 0 {
+                                    remaining
+                                } else {
+                                    let share = traded * order.quantity / eligible_quantity;
+                                    let share = if whole { share.floor() } else { share };
+                                    share.min(remaining)
+                                };
+                                remaining -= share;
+                                share
+                            })
+                            .collect();
+
+                        for (resting_order, share) in level.iter_mut().zip(allocations) {
+                            if share > 0.0 {
+                                if let Some(fill) = resting_order.fill_up_to(
+                                    &mut incoming_order,
+                                    share,
+                                    &self.fill_price_rule,
+                                    self.tick,
+                                    timestamp,
+                                ) {
+                                    fills.push(fill);
+                                }
+                            }
+                        }
+                        incoming_order.replenish();
+
+                        // Refreshed iceberg slices move behind the rest of the level
+                        let mut replenished = Vec::new();
+                        for resting_order in level.iter_mut() {
+                            if resting_order.replenish() {
+                                replenished.push(resting_order.clone());
+                            } else if resting_order.is_open() {
+                                queue.push_back(resting_order.clone());
+                            }
+                        }
+                        queue.extend(replenished);
+                        level
+                    }
+                };
 
-                // Remove the price level if the queue is empty
                 if queue.is_empty() {
-                    target_book.remove(&order.price_in_ticks);
+                    opposite_book.remove(&best_price);
+                }
+
+                touched
+            };
+
+            // Update the resting orders and incoming order in the `orders` map
+            for resting_order in touched {
+                if resting_order.status == OrderStatus::Canceled {
+                    self.orders.remove(&resting_order.id);
+                    self.self_trade_canceled.push(resting_order);
+                } else {
+                    sync_fill_state(&mut self.orders, &resting_order);
                 }
             }
+            sync_fill_state(&mut self.orders, &incoming_order);
+        }
 
-            // Take ownership of the modified order for updating outside the borrow
-            canceled_order = Some(order.clone());
+        let rests = incoming_order.time_in_force == TimeInForce::GTC && !min_fill_blocked;
+        if incoming_order.status == OrderStatus::Canceled {
+            // Canceled by self-trade prevention
+            self.orders.remove(&incoming_order.id);
+            self.self_trade_canceled.push(incoming_order);
+        } else if incoming_order.is_open() && !rests {
+            // Cancel the unfilled remainder and drop it from `orders` entirely
+            incoming_order.status = OrderStatus::Canceled;
+            self.orders.remove(&incoming_order.id);
+        } else if incoming_order.is_open() {
+            // Keep one copy in `orders` and move the order itself into the book
+            self.update_order(&incoming_order);
+            let side = incoming_order.side.clone();
+            self.book_side_mut(&side)
+                .entry(incoming_order.price_in_ticks)
+                .or_default()
+                .push_back(incoming_order);
+        } else {
+            // Always ensure the incoming order is updated in `orders` at the end
+            self.update_order(&incoming_order);
         }
 
-        if let Some(order) = canceled_order {
-            self.orders.remove(&order.id);
-            return true; // Order successfully canceled
+        self.sequence_fills(&mut fills);
+        self.charge_fees(&mut fills);
+        if let Some(fill) = fills.last() {
+            self.last_trade = Some(fill.clone());
         }
 
-        false // Order not found
+        Ok(fills)
     }
 
-    /// Retrieves an order by its ID. Returns None if the order is not found.
-    #[pyo3(text_signature = "(self, order_id)")]
-    pub fn get_order(&self, order_id: &str) -> Option<Order> {
-        self.orders.get(order_id).cloned()
+    /// Executes every stop order triggered by the last trade price, appending
+    /// the resulting fills. Fills from triggered stops can move the last trade
+    /// price and trigger further stops, so this repeats until none remain.
+    fn trigger_stops(&mut self, fills: &mut Vec<Fill>, timestamp: Option<u64>) -> PyResult<()> {
+        loop {
+            let last_trade_ticks = self.last_trade_ticks();
+            let Some(index) = self
+                .stop_orders
+                .iter()
+                .position(|order| order.stop_triggered(last_trade_ticks))
+            else {
+                return Ok(());
+            };
+
+            let triggered = self.stop_orders.remove(index);
+            fills.extend(self.match_order(triggered, timestamp)?);
+        }
     }
+}
 
-    /// Helper method to update an order in the `orders` map.
-    fn update_order(&mut self, order: &Order) {
-        self.orders.insert(order.id.clone(), order.clone());
+/// A lazy iterator over the resting orders on one side of an `OrderBook`, in
+/// price-time priority. Orders are cloned one at a time as they are yielded.
+/// The iterator keeps a cursor into the live book, so orders added or removed
+/// ahead of the cursor while iterating are reflected in what it yields. The
+/// cursor remembers the last order yielded, so canceling that order while
+/// iterating does not skip the one behind it.
+#[pyclass]
+pub struct OrderIterator {
+    book: Py<OrderBook>,
+    side: OrderType,
+    cursor: Option<(i64, usize, String)>, // Level, queue index and ID of the last order yielded
+}
+
+#[pymethods]
+impl OrderIterator {
+    /// Returns the iterator itself.
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
     }
 
-    /// Get a list of all buy orders
+    /// Returns the next resting order, or None once the side is exhausted.
+    fn __next__(&mut self, py: Python<'_>) -> Option<Order> {
+        let book = self.book.borrow(py);
+        let levels: Box<dyn Iterator<Item = (&i64, &VecDeque<Order>)>> = match self.side {
+            OrderType::Buy => {
+                let start = self
+                    .cursor
+                    .as_ref()
+                    .map_or(i64::MAX, |(price, _, _)| *price);
+                Box::new(book.buy_orders.range(..=start).rev())
+            }
+            OrderType::Sell => {
+                let start = self
+                    .cursor
+                    .as_ref()
+                    .map_or(i64::MIN, |(price, _, _)| *price);
+                Box::new(book.sell_orders.range(start..))
+            }
+        };
+
+        for (price, queue) in levels {
+            let index = match &self.cursor {
+                Some((cursor_price, index, last_id)) if cursor_price == price => {
+                    if queue.get(*index).is_some_and(|order| &order.id == last_id) {
+                        index + 1
+                    } else if let Some(moved) = queue.iter().position(|o| &o.id == last_id) {
+                        moved + 1
+                    } else {
+                        // The last order yielded has left, so the next one took its place
+                        *index
+                    }
+                }
+                _ => 0,
+            };
+            if let Some(order) = queue.get(index) {
+                self.cursor = Some((*price, index, order.id.clone()));
+                return Some(order.clone());
+            }
+        }
+        None
+    }
+}
+
+/// The differences between two order books, as reported by `OrderBook.diff`.
+/// Orders are matched by ID and compared on remaining quantity and status.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct BookDiff {
+    added: Vec<String>,    // IDs tracked only by the other book
+    removed: Vec<String>,  // IDs tracked only by this book
+    modified: Vec<String>, // IDs in both books with a different quantity or status
+}
+
+#[pymethods]
+impl BookDiff {
+    /// Getter for the IDs of orders tracked only by the other book.
     #[getter]
-    pub fn get_buy_orders(&self) -> Vec<Order> {
-        self.buy_orders
-            .values()
-            .flat_map(|queue| queue.iter().cloned())
-            .collect()
+    pub fn added(&self) -> Vec<String> {
+        self.added.clone()
     }
 
-    /// Get a list of all sell orders
+    /// Getter for the IDs of orders tracked only by this book.
     #[getter]
-    pub fn get_sell_orders(&self) -> Vec<Order> {
-        self.sell_orders
-            .values()
-            .flat_map(|queue| queue.iter().cloned())
-            .collect()
+    pub fn removed(&self) -> Vec<String> {
+        self.removed.clone()
     }
 
-    /// Helper method to get best bid
-    fn best_bid(&self) -> Option<(i64, f64)> {
-        self.buy_orders.iter().next_back().map(|(price, queue)| {
-            (
-                *price,
-                queue.front().map(|order| order.quantity).unwrap_or(0.0),
-            )
-        })
+    /// Getter for the IDs of orders whose remaining quantity or status differ.
+    #[getter]
+    pub fn modified(&self) -> Vec<String> {
+        self.modified.clone()
     }
 
-    /// Helper method to get best ask
-    fn best_ask(&self) -> Option<(i64, f64)> {
-        self.sell_orders.iter().next().map(|(price, queue)| {
-            (
-                *price,
-                queue.front().map(|order| order.quantity).unwrap_or(0.0),
-            )
-        })
+    /// Checks whether the two books track the same orders in the same state.
+    #[pyo3(text_signature = "($self)")]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
     }
 
-    /// Helper method to calculate total buy volume
-    fn buy_volume(&self) -> f64 {
-        self.buy_orders
-            .values()
-            .flat_map(|queue| queue.iter())
-            .map(|order| order.quantity)
-            .sum()
+    /// Returns a string representation of the diff.
+    fn __repr__(&self) -> String {
+        format!(
+            "BookDiff(added={}, removed={}, modified={})",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len()
+        )
     }
+}
 
-    /// Helper method to calculate total sell volume
-    fn sell_volume(&self) -> f64 {
-        self.sell_orders
-            .values()
-            .flat_map(|queue| queue.iter())
-            .map(|order| order.quantity)
-            .sum()
+/// The best bid and offer of an order book at one moment, as reported by
+/// `OrderBook.quote`. Sizes are the total visible quantity at each price. On
+/// a one-sided book, the empty side has no price and a size of zero.
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    bid_price: Option<f64>, // Best bid, if any
+    bid_size: f64,          // Total visible quantity at the best bid
+    ask_price: Option<f64>, // Best ask, if any
+    ask_size: f64,          // Total visible quantity at the best ask
+    timestamp: u64,         // When the quote was taken, in nanoseconds since the epoch
+}
+
+#[pymethods]
+impl Quote {
+    /// Getter for the best bid price, or None if there are no bids.
+    #[getter]
+    pub fn bid_price(&self) -> Option<f64> {
+        self.bid_price
     }
 
-    /// Calculate the current spread in the order book.
-    /// Returns None if there are no orders on either side.
-    /// The spread is returned in the same units as the prices (not ticks).
-    #[pyo3(text_signature = "($self)")]
-    fn spread(&self) -> Option<f64> {
-        match (self.best_bid(), self.best_ask()) {
-            (Some((bid_price, _)), Some((ask_price, _))) => {
-                // Convert from tick difference to price difference
-                let spread_in_ticks = ask_price - bid_price;
-                Some(spread_in_ticks as f64 * self.tick_size)
-            }
-            _ => None,
-        }
+    /// Getter for the total quantity at the best bid.
+    #[getter]
+    pub fn bid_size(&self) -> f64 {
+        self.bid_size
     }
 
-    /// Return the tick size for informational purposes
+    /// Getter for the best ask price, or None if there are no asks.
     #[getter]
-    pub fn tick_size(&self) -> f64 {
-        self.tick_size
+    pub fn ask_price(&self) -> Option<f64> {
+        self.ask_price
     }
 
-    /// Returns a string representation of the order book.
-    fn __repr__(&self) -> String {
-        let best_bid = self
-            .best_bid()
-            .map(|(price, qty)| format!("{:.2} @ {}", qty, price * self.tick_size as i64))
-            .unwrap_or_else(|| "None".to_string());
+    /// Getter for the total quantity at the best ask.
+    #[getter]
+    pub fn ask_size(&self) -> f64 {
+        self.ask_size
+    }
 
-        let best_ask = self
-            .best_ask()
-            .map(|(price, qty)| format!("{:.2} @ {}", qty, price * self.tick_size as i64))
-            .unwrap_or_else(|| "None".to_string());
+    /// Getter for when the quote was taken, in nanoseconds since the epoch.
+    #[getter]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
 
-        let spread = match (self.best_bid(), self.best_ask()) {
-            (Some((bid, _)), Some((ask, _))) => {
-                format!("{:.4}", (ask - bid) as f64 * self.tick_size)
-            }
-            _ => "None".to_string(),
-        };
+    /// Serializes the quote to a JSON string, e.g. for logging.
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(self).map_err(|err| {
+            pyo3::exceptions::PyValueError::new_err(format!("Failed to serialize quote: {}", err))
+        })
+    }
 
+    /// Returns a string representation of the quote.
+    fn __repr__(&self) -> String {
         format!(
-            "Best Bid: {}, Best Ask: {} (Spread: {})\nOpen Buy Volume: {:.2}, Open Sell Volume: {:.2}",
-            best_bid,
-            best_ask,
-            spread,
-            self.buy_volume(),
-            self.sell_volume()
+            "Quote(bid={:?} x {}, ask={:?} x {})",
+            self.bid_price, self.bid_size, self.ask_price, self.ask_size
         )
     }
 }
 
 impl Default for OrderBook {
     fn default() -> Self {
-        Self::new(0.01)
+        Self::new(
+            0.01,
+            SelfTradePrevention::CancelNewest,
+            MatchingMode::Fifo,
+            FillPriceRule::Seller,
+            None,
+            None,
+            None,
+            None,
+            LevelPriority::Time,
+            false,
+            0.0,
+            0.0,
+            None,
+            CrossedBookPolicy::Resolve,
+            1,
+            false,
+        )
+        .expect("Default configuration is valid")
     }
 }
 
@@ -571,5 +5050,25 @@ fn litebook(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<OrderBook>()?;
     m.add_class::<OrderType>()?;
     m.add_class::<OrderStatus>()?;
+    m.add_class::<TimeInForce>()?;
+    m.add_class::<OrderKind>()?;
+    m.add_class::<SelfTradePrevention>()?;
+    m.add_class::<MatchingMode>()?;
+    m.add_class::<FillPriceRule>()?;
+    m.add_class::<CrossedBookPolicy>()?;
+    m.add_class::<CancelOutcome>()?;
+    m.add_class::<LevelPriority>()?;
+    m.add_class::<RoundingMode>()?;
+    m.add_class::<OrderRejectReason>()?;
+    m.add_class::<PegReference>()?;
+    m.add_class::<Event>()?;
+    m.add_class::<OrderIterator>()?;
+    m.add_class::<BookDiff>()?;
+    m.add_class::<Quote>()?;
+    m.add(
+        "OrderRejectedError",
+        m.py().get_type::<OrderRejectedError>(),
+    )?;
+    m.add("PriceBandError", m.py().get_type::<PriceBandError>())?;
     Ok(())
 }
@@ -1,5 +1,9 @@
 """Test the litebook Order, OrderBook, OrderStatus, OrderType modules."""
 
+import csv
+import io
+import json
+
 import litebook as lb
 import pytest
 
@@ -16,6 +20,32 @@ def test_default_orderbook():
     assert book.tick_size == 0.01
 
 
+@pytest.mark.parametrize(
+    "tick_size", [0.0, -0.01, float("nan"), float("inf"), 1e-16, 1 / 3, 1e20]
+)
+def test_invalid_tick_size(tick_size):
+    """Test that a tick size that is not positive, finite and exact is rejected."""
+    with pytest.raises(ValueError):
+        lb.OrderBook(tick_size=tick_size)
+
+
+@pytest.mark.parametrize(
+    "tick_size, ticks, price",
+    [(0.05, 202, 10.10), (0.1, 3, 0.3), (0.01, 1005, 10.05), (0.25, 7, 1.75), (5.0, 3, 15.0)],
+)
+def test_tick_price_conversion_is_exact(tick_size, ticks, price):
+    """Test that ticks and prices convert exactly, without float multiplication error."""
+    book = lb.OrderBook(tick_size=tick_size)
+    assert book.tick_size == tick_size
+    assert book.ticks_to_price(ticks) == price  # e.g. 202 * 0.05 == 10.100000000000001
+    assert book.price_to_ticks(price) == ticks
+
+    book.add(book.create_order(lb.OrderType.Sell, price=price, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=price, quantity=1.0))
+    assert fills[0].price == price
+    assert book.last_trade_price == price
+
+
 def test_order_creation():
     """Test that orders can be created through the OrderBook."""
     book = lb.OrderBook(tick_size=0.05)
@@ -82,6 +112,22 @@ def test_partial_fill():
     )  # Status should change to filled
 
 
+def test_many_small_fills_leave_no_dust():
+    """Test that small fills summing exactly to an order's size fully fill it."""
+    book = lb.OrderBook(tick_size=0.05)
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0)
+    book.add(sell_order)
+
+    # Subtracting 0.1 ten times from 1.0 leaves about 1e-16 in floating point
+    for _ in range(10):
+        book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=0.1))
+
+    assert book.get_order(sell_order.id).status == lb.OrderStatus.Filled
+    assert book.get_order(sell_order.id).quantity == 0.0
+    assert book.sell_orders == []
+    assert book.buy_orders == []
+
+
 def test_price_matching():
     """Test price matching logic."""
     book = lb.OrderBook(tick_size=0.05)
@@ -106,6 +152,20 @@ def test_price_matching():
     assert len(fills) == 0  # Should not match
 
 
+def test_price_matching_maker_price():
+    """Test that the Maker rule fills at the resting order's price instead of the sell price."""
+    assert lb.OrderBook().fill_price_rule == lb.FillPriceRule.Seller
+    book = lb.OrderBook(tick_size=0.05, fill_price_rule=lb.FillPriceRule.Maker)
+
+    buy_order = book.create_order(lb.OrderType.Buy, price=10.10, quantity=5.0)
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.05, quantity=5.0)
+    book.add(buy_order)
+    fills = book.add(sell_order)
+
+    assert len(fills) == 1
+    assert fills[0].price == 10.10  # Fills at the resting buy's price
+
+
 def test_order_validation():
     """Test order validation rules."""
     book = lb.OrderBook(tick_size=0.05)
@@ -150,8 +210,11 @@ def test_cancel_order(order_book: lb.OrderBook):
     assert buy_order.status == lb.OrderStatus.Open
 
     # Cancel the order
-    was_canceled = book.cancel(buy_order.id)
-    assert was_canceled  # Should return True
+    canceled = book.cancel(buy_order.id)
+    assert canceled is not None  # Should return the canceled order
+    assert canceled.id == buy_order.id
+    assert canceled.status == lb.OrderStatus.Canceled
+    assert canceled.quantity == 5.0
     assert (
         book.get_order(buy_order.id) is None
     )  # Should no longer exist in the order book
@@ -165,8 +228,8 @@ def test_cancel_order(order_book: lb.OrderBook):
     book.add(sell_order)
 
     # Cancel the sell order
-    was_canceled = book.cancel(sell_order.id)
-    assert was_canceled  # Should return True
+    canceled = book.cancel(sell_order.id)
+    assert canceled is not None  # Should return the canceled order
     assert (
         book.get_order(sell_order.id) is None
     )  # Should no longer exist in the order book
@@ -176,4 +239,2609 @@ def test_cancel_order(order_book: lb.OrderBook):
     assert all(order.id != sell_order.id for order in sell_orders)
 
     # Test canceling a non-existent order
-    assert not book.cancel("non_existent_id")  # Should return False
+    assert book.cancel("non_existent_id") is None  # Should return None
+
+
+def test_ioc_order_cancels_remainder(order_book: lb.OrderBook):
+    """Test that an IOC order fills what it can and cancels the rest."""
+    book = order_book
+
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    book.add(sell_order)
+
+    ioc_buy = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=5.0, time_in_force=lb.TimeInForce.IOC
+    )
+    assert ioc_buy.time_in_force == lb.TimeInForce.IOC
+    fills = book.add(ioc_buy)
+
+    assert len(fills) == 1
+    assert fills[0].quantity == 3.0
+
+    # The remainder never rests and the order is dropped from the book
+    assert book.buy_orders == []
+    assert book.get_order(ioc_buy.id) is None
+
+
+def test_ioc_order_without_liquidity(order_book: lb.OrderBook):
+    """Test that an IOC order with nothing to match is canceled outright."""
+    book = order_book
+
+    ioc_sell = book.create_order(
+        lb.OrderType.Sell, price=10.00, quantity=5.0, time_in_force=lb.TimeInForce.IOC
+    )
+    fills = book.add(ioc_sell)
+
+    assert fills == []
+    assert book.sell_orders == []
+    assert book.get_order(ioc_sell.id) is None
+
+
+def test_fok_order_killed_without_full_liquidity(order_book: lb.OrderBook):
+    """Test that a FOK order that cannot fully fill leaves the book untouched."""
+    book = order_book
+
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    book.add(sell_order)
+
+    fok_buy = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=5.0, time_in_force=lb.TimeInForce.FOK
+    )
+    fills = book.add(fok_buy)
+
+    assert fills == []
+    assert book.get_order(fok_buy.id) is None
+    assert book.get_order(sell_order.id).quantity == 3.0
+    assert len(book.sell_orders) == 1
+
+
+def test_fok_order_fills_exact_liquidity(order_book: lb.OrderBook):
+    """Test that a FOK order fills when its quantity equals available liquidity."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=3.0))
+    # Priced above the buyer's limit, so it must not count as available
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=4.0))
+
+    assert book.available_liquidity(lb.OrderType.Buy, 201) == 5.0
+
+    fok_buy = book.create_order(
+        lb.OrderType.Buy, price=10.05, quantity=5.0, time_in_force=lb.TimeInForce.FOK
+    )
+    fills = book.add(fok_buy)
+
+    assert sum(fill.quantity for fill in fills) == 5.0
+    assert book.get_order(fok_buy.id).status == lb.OrderStatus.Filled
+    assert len(book.sell_orders) == 1
+
+    # Liquidity is judged on the FOK order's matching price, not its display price
+    fok_offset = lb.Order(
+        lb.OrderType.Buy, 200, 4.0, time_in_force=lb.TimeInForce.FOK, matching_price_in_ticks=202
+    )
+    assert sum(fill.quantity for fill in book.add(fok_offset)) == 4.0
+    assert book.sell_orders == []
+
+
+def test_liquidity_within(order_book: lb.OrderBook):
+    """Test summing the liquidity an aggressor can reach up to a price limit."""
+    book = order_book
+    assert book.liquidity_within(lb.OrderType.Buy, 10.00) == 0.0
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.95, quantity=4.0))
+
+    assert book.liquidity_within(lb.OrderType.Buy, 9.95) == 0.0
+    assert book.liquidity_within(lb.OrderType.Buy, 10.00) == 2.0
+    assert book.liquidity_within(lb.OrderType.Buy, 10.05) == 5.0
+    assert book.liquidity_within(lb.OrderType.Buy, 10.05) == book.available_liquidity(
+        lb.OrderType.Buy, 201
+    )
+    assert book.liquidity_within(lb.OrderType.Sell, 9.95) == 4.0
+    assert book.liquidity_within(lb.OrderType.Sell, 9.90) == 5.0
+
+
+def test_post_only_order_rejected_when_crossing(order_book: lb.OrderBook):
+    """Test that a post-only order that would take liquidity is rejected."""
+    book = order_book
+
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    book.add(sell_order)
+
+    post_buy = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=5.0, post_only=True
+    )
+    with pytest.raises(ValueError):
+        book.add(post_buy)
+
+    # Nothing executed and the rejected order was not inserted
+    assert book.get_order(post_buy.id) is None
+    assert book.buy_orders == []
+    assert book.get_order(sell_order.id).quantity == 3.0
+
+
+def test_post_only_order_rests(order_book: lb.OrderBook):
+    """Test that a non-crossing post-only order rests normally."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0))
+
+    post_sell = book.create_order(
+        lb.OrderType.Sell, price=10.05, quantity=5.0, post_only=True
+    )
+    assert post_sell.post_only
+    fills = book.add(post_sell)
+
+    assert fills == []
+    assert book.get_order(post_sell.id).status == lb.OrderStatus.Open
+    assert len(book.sell_orders) == 1
+
+
+def test_iceberg_order_displays_slice(order_book: lb.OrderBook):
+    """Test that only the display quantity of an iceberg order is visible."""
+    book = order_book
+
+    iceberg = book.create_iceberg_order(
+        lb.OrderType.Sell, price=10.00, quantity=10.0, display_quantity=2.0
+    )
+    assert iceberg.quantity == 2.0
+    assert iceberg.hidden_quantity == 8.0
+    assert iceberg.display_quantity == 2.0
+    book.add(iceberg)
+
+    assert [order.quantity for order in book.sell_orders] == [2.0]
+    assert "Sell 2 @ 200" in repr(book.get_order(iceberg.id))
+
+    with pytest.raises(ValueError):
+        book.create_iceberg_order(
+            lb.OrderType.Sell, price=10.00, quantity=1.0, display_quantity=2.0
+        )
+
+
+def test_iceberg_order_replenishes_at_back(order_book: lb.OrderBook):
+    """Test that a consumed iceberg slice is replenished behind later orders."""
+    book = order_book
+
+    iceberg = book.create_iceberg_order(
+        lb.OrderType.Sell, price=10.00, quantity=5.0, display_quantity=2.0
+    )
+    book.add(iceberg)
+    plain = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    book.add(plain)
+
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    assert [fill.quantity for fill in fills] == [2.0]
+
+    # The replenished slice now queues behind the plain order
+    assert [order.id for order in book.sell_orders] == [plain.id, iceberg.id]
+    resting = book.get_order(iceberg.id)
+    assert resting.quantity == 2.0
+    assert resting.hidden_quantity == 1.0
+
+    # Sweeping the level consumes the plain order and the rest of the iceberg
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=10.0))
+    assert sum(fill.quantity for fill in fills) == 4.0
+    assert book.get_order(iceberg.id).status == lb.OrderStatus.Filled
+    assert book.sell_orders == []
+
+
+def test_market_order_sweeps_and_cancels_remainder(order_book: lb.OrderBook):
+    """Test that a market order trades at resting prices and never rests."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.50, quantity=2.0))
+
+    market_buy = book.create_market_order(lb.OrderType.Buy, quantity=5.0)
+    assert market_buy.kind == lb.OrderKind.Market
+    fills = book.add(market_buy)
+
+    assert [fill.price for fill in fills] == [10.00, 10.50]
+    assert book.buy_orders == []
+    assert book.get_order(market_buy.id) is None
+
+
+def test_market_order_slippage_collar(order_book: lb.OrderBook):
+    """Test that a collared market order stops sweeping a thin book at the collar."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=12.00, quantity=5.0))
+
+    # The collar is measured from the best ask of 10.00 at submission
+    market_buy = book.create_market_order(
+        lb.OrderType.Buy, quantity=5.0, max_slippage_ticks=2
+    )
+    assert market_buy.max_slippage_ticks == 2
+    fills = book.add(market_buy)
+    assert [fill.price for fill in fills] == [10.00, 10.05, 10.10]
+    assert book.get_order(market_buy.id) is None
+    assert book.best_ask == (12.00, 5.0)
+
+    # A zero collar only takes the touch
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=8.95, quantity=1.0))
+    fills = book.add(
+        book.create_market_order(lb.OrderType.Sell, quantity=2.0, max_slippage_ticks=0)
+    )
+    assert [fill.price for fill in fills] == [9.00]
+    assert book.best_bid == (8.95, 1.0)
+
+    with pytest.raises(lb.OrderRejectedError):
+        book.create_market_order(lb.OrderType.Buy, quantity=1.0, max_slippage_ticks=-1)
+
+
+def test_stop_loss_triggers_on_last_trade(order_book: lb.OrderBook):
+    """Test that a stop-loss stays dormant until the last trade crosses its stop."""
+    book = order_book
+
+    stop_sell = book.create_stop_order(lb.OrderType.Sell, stop_price=9.50, quantity=1.0)
+    assert book.add(stop_sell) == []
+    assert [order.id for order in book.stop_orders] == [stop_sell.id]
+    assert book.get_order(stop_sell.id).status == lb.OrderStatus.Open
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=5.0))
+
+    # A trade above the stop price leaves the stop dormant
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    assert len(fills) == 1
+    assert len(book.stop_orders) == 1
+
+    # A trade at the stop price triggers it into a market sell
+    book.add(book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Sell, price=9.50, quantity=1.0))
+    assert [fill.price for fill in fills] == [9.50, 9.00]
+    assert fills[1].sell_id == stop_sell.id
+    assert book.stop_orders == []
+
+
+def test_stop_orders_cascade(order_book: lb.OrderBook):
+    """Test that fills from a triggered stop can trigger further stops."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+
+    first = book.create_stop_order(lb.OrderType.Sell, stop_price=10.00, quantity=1.0)
+    second = book.create_stop_order(
+        lb.OrderType.Sell, stop_price=9.50, quantity=1.0, limit_price=9.00
+    )
+    book.add(first)
+    book.add(second)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+
+    # The trade at 10.00 triggers the first stop, whose fill at 9.50 triggers the second
+    assert [fill.price for fill in fills] == [10.00, 9.50, 9.00]
+    assert book.stop_orders == []
+    assert book.buy_orders == []
+
+
+def test_cancel_detailed(order_book: lb.OrderBook):
+    """Test cancel outcomes and book consistency after canceling a partial fill."""
+    book = order_book
+    resting = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0)
+    book.add(resting)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+
+    outcome, canceled = book.cancel_detailed(resting.id)
+    assert outcome == lb.CancelOutcome.Canceled
+    assert canceled.status == lb.OrderStatus.Canceled
+    assert canceled.quantity == 3.0
+    assert book.get_order(resting.id) is None
+    assert book.sell_orders == []
+    assert book.validate()
+
+    # Unknown and no-longer-open orders are not found
+    assert book.cancel_detailed(resting.id) == (lb.CancelOutcome.NotFound, None)
+    assert book.cancel("missing") is None
+
+    # An order tracked at a price it does not rest at is reported, not dropped
+    order = book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0)
+    book.add(order)
+    snapshot = json.loads(book.to_json())
+    snapshot["orders"][order.id]["price_in_ticks"] = 180
+    corrupted = lb.OrderBook.from_json(json.dumps(snapshot))
+    outcome, tracked = corrupted.cancel_detailed(order.id)
+    assert outcome == lb.CancelOutcome.FoundButNotInQueue
+    assert tracked.price_in_ticks == 180
+    assert corrupted.cancel(order.id) is None
+    assert corrupted.best_bid == (9.50, 1.0)
+
+
+def test_compact(order_book: lb.OrderBook):
+    """Test that compact() is a no-op on a healthy book and repairs a corrupted one."""
+    book = order_book
+    order = book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0)
+    book.add(order)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    assert book.compact() == 0
+
+    # An empty level and a lookup entry that disagrees with its queued copy
+    snapshot = json.loads(book.to_json())
+    snapshot["buy_orders"]["180"] = []
+    snapshot["orders"][order.id]["quantity"] = 7.0
+    corrupted = lb.OrderBook.from_json(json.dumps(snapshot))
+    assert not corrupted.validate()
+
+    assert corrupted.compact() == 2
+    assert corrupted.validate()
+    assert corrupted.num_levels(lb.OrderType.Buy) == 1
+    assert corrupted.get_order(order.id).quantity == 1.0
+    assert corrupted.compact() == 0
+
+
+def test_cancel_stop_order(order_book: lb.OrderBook):
+    """Test that an untriggered stop order can be canceled."""
+    book = order_book
+
+    stop_buy = book.create_stop_order(lb.OrderType.Buy, stop_price=11.00, quantity=1.0)
+    book.add(stop_buy)
+
+    assert book.cancel(stop_buy.id) is not None
+    assert book.stop_orders == []
+    assert book.get_order(stop_buy.id) is None
+
+
+def test_expire_good_till_date_orders(order_book: lb.OrderBook):
+    """Test that expire() cancels only orders whose expiry has passed."""
+    book = order_book
+
+    early = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=1.0, expires_at=100, timestamp=10
+    )
+    late = book.create_order(
+        lb.OrderType.Sell, price=10.50, quantity=1.0, expires_at=200, timestamp=20
+    )
+    forever = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    assert early.timestamp == 10
+    assert early.expires_at == 100
+    assert forever.expires_at is None
+    for order in (early, late, forever):
+        book.add(order)
+
+    assert book.expire(99) == []
+
+    expired = book.expire(100)
+    assert [order.id for order in expired] == [early.id]
+    assert expired[0].status == lb.OrderStatus.Canceled
+    assert book.get_order(early.id) is None
+    assert [order.id for order in book.buy_orders] == [forever.id]
+
+    expired = book.expire(1_000)
+    assert [order.id for order in expired] == [late.id]
+    assert book.sell_orders == []
+
+
+def test_expire_skips_filled_and_canceled(order_book: lb.OrderBook):
+    """Test that orders filled or canceled before their expiry are not expired."""
+    book = order_book
+
+    filled = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0, expires_at=100)
+    canceled = book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0, expires_at=100)
+    resting = book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0, expires_at=100)
+    for order in (filled, canceled, resting):
+        book.add(order)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.cancel(canceled.id)
+
+    assert [order.id for order in book.expire(100)] == [resting.id]
+    assert book.expire(100) == []
+
+
+def test_expire_with_many_deadlines():
+    """Test that expire() removes exactly the orders past their deadline."""
+    book = lb.OrderBook(tick_size=0.01)
+    count = 1_000
+    for i in range(count):
+        price = 1.0 + (i % 500) * 0.01
+        order = book.create_order(lb.OrderType.Buy, price, 1.0, expires_at=1_000 + i)
+        book.add(order)
+
+    assert book.expire(999) == []
+    assert len(book) == count
+
+    assert len(book.expire(1_000 + count // 2 - 1)) == count // 2
+    assert len(book) == count // 2
+    assert len(book.expire(10**12)) == count // 2
+    assert len(book) == 0
+
+
+def test_add_at_stamps_fills(order_book: lb.OrderBook):
+    """Test that add_at stamps every fill, including triggered stops, deterministically."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+    book.add(book.create_stop_order(lb.OrderType.Sell, stop_price=10.00, quantity=1.0))
+
+    sell_order = book.create_order(
+        lb.OrderType.Sell, price=10.00, quantity=1.0, timestamp=42
+    )
+    fills = book.add_at(sell_order, 1_000)
+
+    assert len(fills) == 2
+    assert all(fill.timestamp == 1_000 for fill in fills)
+
+
+def test_depth_aggregates_levels():
+    """Test that depth() aggregates quantity per level in price priority."""
+    book = lb.OrderBook(tick_size=0.5)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.0, quantity=4.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=12.5, quantity=1.5))
+
+    bids, asks = book.depth(1)
+    assert bids == [(10.0, 5.0)]
+    assert asks == [(11.0, 4.0)]
+
+    # Asking for more levels than exist returns whatever is available
+    bids, asks = book.depth(10)
+    assert bids == [(10.0, 5.0), (9.0, 1.0)]
+    assert asks == [(11.0, 4.0), (12.5, 1.5)]
+
+    assert lb.OrderBook().depth(3) == ([], [])
+
+
+def test_level():
+    """Test that level() returns a single level counted from the touch."""
+    book = lb.OrderBook(tick_size=0.5)
+    book.add(book.create_order(lb.OrderType.Buy, price=9.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.0, quantity=4.0))
+
+    assert book.level(lb.OrderType.Buy, 0) == (10.0, 5.0)
+    assert book.level(lb.OrderType.Buy, 1) == (9.0, 1.0)
+    assert book.level(lb.OrderType.Buy, 2) is None
+    assert book.level(lb.OrderType.Sell, 0) == (11.0, 4.0)
+    assert book.level(lb.OrderType.Sell, 1) is None
+
+
+def test_tick_multiple():
+    """Test that limit prices must sit on the book's coarser tick grid."""
+    assert lb.OrderBook().tick_multiple == 1
+    with pytest.raises(ValueError, match="tick_multiple"):
+        lb.OrderBook(tick_multiple=0)
+
+    book = lb.OrderBook(tick_size=0.01, tick_multiple=5)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+
+    off_grid = lb.Order(lb.OrderType.Buy, 1003, 1.0)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.add(off_grid)
+    assert excinfo.value.reason == lb.OrderRejectReason.PriceOffTick
+    assert off_grid.id not in book
+
+    # Market orders carry no price and are never rejected
+    fills = book.add(book.create_market_order(lb.OrderType.Buy, 1.0))
+    assert fills[0].price == pytest.approx(10.05)
+
+
+def test_allow_negative_prices(order_book: lb.OrderBook):
+    """Test that a book allowing negative prices rests and crosses below zero."""
+    assert not order_book.allow_negative_prices
+    with pytest.raises(lb.OrderRejectedError):
+        order_book.create_order(lb.OrderType.Buy, price=-1.00, quantity=1.0)
+
+    book = lb.OrderBook(tick_size=0.05, allow_negative_prices=True)
+    ask = book.create_order(lb.OrderType.Sell, price=-1.00, quantity=2.0)
+    assert ask.price_in_ticks == -20
+    book.add(ask)
+    book.add(book.create_order(lb.OrderType.Buy, price=-1.50, quantity=1.0))
+    assert book.spread() == pytest.approx(0.50)
+
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=0.0, quantity=1.0))
+    assert [(fill.price, fill.quantity) for fill in fills] == [(-1.00, 1.0)]
+    assert book.best_ask == (-1.00, 1.0)
+    assert book.best_bid == (-1.50, 1.0)
+
+    # The Order constructor still requires positive prices, and a default book
+    # rejects negative orders built by a book that allows them
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        lb.Order(lb.OrderType.Buy, -5, 1.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.NonPositivePrice
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        order_book.add(book.get_order(ask.id))
+    assert excinfo.value.reason == lb.OrderRejectReason.NonPositivePrice
+    assert len(order_book) == 0
+
+
+def test_ladder():
+    """Test that ladder() maps every level's price to its total quantity."""
+    book = lb.OrderBook(tick_size=0.5)
+    assert book.ladder() == ({}, {})
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=12.5, quantity=1.5))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.0, quantity=4.0))
+
+    bids, asks = book.ladder()
+    assert bids == {10.0: 5.0, 9.0: 1.0}
+    assert asks == {11.0: 4.0, 12.5: 1.5}
+    assert list(bids) == [10.0, 9.0]
+    assert list(asks) == [11.0, 12.5]
+
+
+def test_cumulative_depth():
+    """Test that cumulative_depth() accumulates quantity outward from the touch."""
+    book = lb.OrderBook(tick_size=0.5)
+    assert book.cumulative_depth(lb.OrderType.Buy) == []
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.0, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.0, quantity=4.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=12.5, quantity=1.5))
+
+    assert book.cumulative_depth(lb.OrderType.Buy) == [(10.0, 5.0), (9.0, 6.0)]
+    assert book.cumulative_depth(lb.OrderType.Sell) == [(11.0, 4.0), (12.5, 5.5)]
+
+
+def test_quantity_histogram():
+    """Test that quantity_histogram() buckets both sides by price."""
+    book = lb.OrderBook(tick_size=1.0)
+    assert book.quantity_histogram(10) == []
+
+    book.add(book.create_order(lb.OrderType.Buy, price=85.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=98.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=99.0, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=100.0, quantity=4.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=109.0, quantity=0.5))
+
+    assert book.quantity_histogram(10) == [(80, 1.0, 0.0), (90, 5.0, 0.0), (100, 0.0, 4.5)]
+    assert book.quantity_histogram(100) == [(0, 6.0, 0.0), (100, 0.0, 4.5)]
+    with pytest.raises(ValueError):
+        book.quantity_histogram(0)
+
+
+def test_mid_price(order_book: lb.OrderBook):
+    """Test the best bid, best ask, and mid-price properties."""
+    book = order_book
+    assert book.best_bid is None
+    assert book.best_ask is None
+    assert book.mid_price() is None
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert book.best_bid == (pytest.approx(10.00), 1.0)
+    assert book.mid_price() is None
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0))
+    assert book.best_ask == (pytest.approx(10.10), 1.0)
+    assert book.mid_price() == pytest.approx(10.05)
+
+
+def test_is_crossed(order_book: lb.OrderBook):
+    """Test that continuous matching never crosses the book but an auction can."""
+    book = order_book
+    assert not book.is_crossed()
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.10, quantity=2.0))
+    assert not book.is_crossed()
+
+    book.start_auction()
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    assert book.is_crossed()
+    book.uncross()
+    assert not book.is_crossed()
+
+
+def test_microprice(order_book: lb.OrderBook):
+    """Test that the microprice equals the mid when balanced and leans toward the thin side."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    assert book.microprice() is None
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=2.0))
+    assert book.microprice() == pytest.approx(book.mid_price())
+
+    # Three times the size on the bid, summed across two orders, pulls it toward the ask
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=4.0))
+    assert book.microprice() == pytest.approx(10.075)
+    assert book.microprice() > book.mid_price()
+
+
+def test_quote(order_book: lb.OrderBook):
+    """Test that quote() reports the best bid and offer, including one-sided books."""
+    book = order_book
+    assert book.quote() is None
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    quote = book.quote()
+    assert (quote.bid_price, quote.bid_size) == (10.00, 2.0)
+    assert (quote.ask_price, quote.ask_size) == (None, 0.0)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=4.0))
+    quote = book.quote()
+    assert (quote.bid_price, quote.bid_size) == (10.00, 3.0)
+    assert (quote.ask_price, quote.ask_size) == (10.10, 4.0)
+    assert quote.timestamp > 0
+    assert json.loads(quote.to_json())["ask_size"] == 4.0
+
+
+def test_impact_price():
+    """Test the volume-weighted impact price, including a partially consumed level."""
+    book = lb.OrderBook(tick_size=1.0)
+
+    book.add(book.create_order(lb.OrderType.Sell, price=100.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=101.0, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=98.0, quantity=5.0))
+
+    assert book.impact_price(lb.OrderType.Buy, 2.0) == pytest.approx(100.0)
+    # 2 @ 100 + 1 @ 101
+    assert book.impact_price(lb.OrderType.Buy, 3.0) == pytest.approx(301.0 / 3.0)
+    assert book.impact_price(lb.OrderType.Buy, 5.0) is None
+    assert book.impact_price(lb.OrderType.Sell, 5.0) == pytest.approx(98.0)
+
+    # The book is not mutated
+    assert len(book.sell_orders) == 2
+
+
+def test_reject_reasons(order_book: lb.OrderBook):
+    """Test that rejections raise OrderRejectedError carrying a reason code."""
+    book = order_book
+
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.create_order(lb.OrderType.Buy, price=-1.0, quantity=1.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.NonPositivePrice
+    assert "positive" in str(excinfo.value)
+
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.create_order(lb.OrderType.Buy, price=10.01, quantity=1.0, strict=True)
+    assert excinfo.value.reason == lb.OrderRejectReason.PriceOffTick
+
+    order = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    book.add(order)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.add(order)
+    assert excinfo.value.reason == lb.OrderRejectReason.DuplicateOrderId
+
+    # Rejections remain ValueErrors, and the price band error is one of them
+    assert issubclass(lb.OrderRejectedError, ValueError)
+    assert issubclass(lb.PriceBandError, lb.OrderRejectedError)
+
+
+def test_reduce_only(order_book: lb.OrderBook):
+    """Test that reduce-only orders are capped to the owner's position."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0, owner="mm"))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0, owner="alice"))
+    assert book.position("alice") == 3.0
+    assert book.position("mm") == -3.0
+
+    # A reduce-only sell larger than the long position is trimmed to it
+    exit_order = book.create_order(
+        lb.OrderType.Sell, price=10.50, quantity=5.0, owner="alice", reduce_only=True
+    )
+    assert exit_order.reduce_only
+    book.add(exit_order)
+    assert book.get_order(exit_order.id).quantity == 3.0
+
+    # The working reduce-only order already covers the whole position
+    with pytest.raises(lb.OrderRejectedError):
+        book.add(
+            book.create_order(
+                lb.OrderType.Sell, price=10.50, quantity=1.0, owner="alice", reduce_only=True
+            )
+        )
+
+    # A reduce-only buy would only increase a long position
+    with pytest.raises(lb.OrderRejectedError):
+        book.add(book.create_market_order(lb.OrderType.Buy, 1.0, reduce_only=True))
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.50, quantity=2.0, owner="bob"))
+    assert book.position("alice") == 1.0
+    assert book.position("bob") == 2.0
+
+
+def test_diff(order_book: lb.OrderBook):
+    """Test that diff reports added, removed and modified orders by ID."""
+    book = order_book
+
+    kept = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    dropped = book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0)
+    book.add(kept)
+    book.add(dropped)
+    other = lb.OrderBook.from_json(book.to_json())
+    assert book.diff(other).is_empty()
+
+    other.modify_quantity(kept.id, 4.0)
+    other.cancel(dropped.id)
+    extra = other.create_order(lb.OrderType.Sell, price=10.50, quantity=1.0)
+    other.add(extra)
+
+    diff = book.diff(other)
+    assert diff.added == [extra.id]
+    assert diff.removed == [dropped.id]
+    assert diff.modified == [kept.id]
+    assert not diff.is_empty()
+
+
+def test_max_orders_eviction():
+    """Test that exceeding max_orders evicts the oldest, least aggressive order."""
+    book = lb.OrderBook(tick_size=0.05, max_orders=3)
+    assert book.max_orders == 3
+
+    deep_bid = book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0, timestamp=1)
+    deep_ask = book.create_order(lb.OrderType.Sell, price=11.00, quantity=1.0, timestamp=2)
+    near_bid = book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0, timestamp=3)
+    for order in (deep_bid, deep_ask, near_bid):
+        book.add(order)
+    assert book.evicted == []
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0, timestamp=4))
+    assert len(book) == 3
+    assert [order.id for order in book.evicted] == [deep_bid.id]
+    assert book.evicted[0].status == lb.OrderStatus.Canceled
+    assert deep_bid.id not in book
+
+    # The next add evicts the deepest ask, which is now the oldest candidate
+    book.add(book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0, timestamp=5))
+    assert [order.id for order in book.evicted] == [deep_ask.id]
+
+    with pytest.raises(ValueError):
+        lb.OrderBook(max_orders=0)
+
+
+def test_add_group_all_or_none(order_book: lb.OrderBook):
+    """Test that an all-or-none group commits only when every leg fills in full."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+
+    def leg(side, price, quantity):
+        return book.create_order(side, price, quantity, time_in_force=lb.TimeInForce.IOC)
+
+    # The second buy leg needs liquidity the first leg has already taken
+    legs = [leg(lb.OrderType.Buy, 10.00, 2.0), leg(lb.OrderType.Buy, 10.00, 1.0)]
+    assert book.add_group(legs, all_or_none=True) == []
+    assert book.best_ask == (10.00, 2.0)
+    assert book.trade_count() == 0
+
+    legs = [leg(lb.OrderType.Buy, 10.00, 2.0), leg(lb.OrderType.Sell, 9.00, 1.0)]
+    fills = book.add_group(legs, all_or_none=True)
+    assert [fill.quantity for fill in fills] == [2.0, 1.0]
+    assert book.best_ask is None and book.best_bid is None
+
+    # Legs reusing the ID of a filled order are rejected, even though it no longer rests
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.add_group([legs[0]], all_or_none=True)
+    assert excinfo.value.reason == lb.OrderRejectReason.DuplicateOrderId
+    assert book.best_ask == (10.00, 2.0)
+    book.cancel(book.sell_orders[0].id)
+
+    # Without all_or_none, legs are added independently and may rest
+    rest = book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0)
+    assert book.add_group([rest]) == []
+    assert rest.id in book
+
+
+def test_stats(order_book: lb.OrderBook):
+    """Test the activity counters and their reset."""
+    book = order_book
+
+    resting = book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0)
+    book.add(resting)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    book.cancel(resting.id)
+
+    assert book.stats() == {
+        "orders_added": 3,
+        "orders_canceled": 1,
+        "fills": 1,
+        "volume": 2.0,
+        "times_emptied": 1,
+        "crosses_resolved": 0,
+    }
+
+    book.reset_stats()
+    assert set(book.stats().values()) == {0}
+
+
+def test_min_fill_quantity(order_book: lb.OrderBook):
+    """Test that executions below the minimum stop matching and cancel the rest."""
+    book = order_book
+    big = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    small = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    book.add(big)
+    book.add(small)
+
+    order = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0, min_fill_quantity=2.0)
+    assert order.min_fill_quantity == 2.0
+    fills = book.add(order)
+    assert [(fill.sell_id, fill.quantity) for fill in fills] == [(big.id, 3.0)]
+    # The GTC remainder is canceled instead of resting across the small ask
+    assert order.id not in book
+    assert book.best_ask == (10.00, 1.0)
+
+    # An IOC blocked by the small ask trades nothing and cancels as usual
+    ioc = book.create_order(
+        lb.OrderType.Buy,
+        price=10.00,
+        quantity=2.0,
+        time_in_force=lb.TimeInForce.IOC,
+        min_fill_quantity=2.0,
+    )
+    assert book.add(ioc) == []
+    assert ioc.id not in book
+
+    # A final remainder smaller than the minimum may still complete
+    book.cancel(small.id)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0))
+    order = book.create_order(lb.OrderType.Buy, price=10.10, quantity=4.0, min_fill_quantity=3.0)
+    assert [fill.quantity for fill in book.add(order)] == [3.0, 1.0]
+
+    # Without crossing liquidity the order rests as usual
+    resting = book.create_order(lb.OrderType.Buy, price=9.50, quantity=2.0, min_fill_quantity=2.0)
+    book.add(resting)
+    assert resting.id in book
+
+    with pytest.raises(lb.OrderRejectedError):
+        book.create_order(
+            lb.OrderType.Buy,
+            price=10.00,
+            quantity=2.0,
+            time_in_force=lb.TimeInForce.FOK,
+            min_fill_quantity=1.0,
+        )
+    with pytest.raises(lb.OrderRejectedError):
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0, min_fill_quantity=3.0)
+
+
+def test_matching_price(order_book: lb.OrderBook):
+    """Test that orders match on their matching price but trade at the display price."""
+    book = order_book
+
+    # Without a matching price, both prices coincide
+    plain = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    assert plain.matching_price_in_ticks == plain.price_in_ticks
+
+    # A sell displayed at 10.10 but willing to match at 10.00
+    implied = book.create_order(
+        lb.OrderType.Sell, price=10.10, quantity=2.0, matching_price=10.00
+    )
+    assert implied.price_in_ticks == 202
+    assert implied.matching_price_in_ticks == 200
+    assert implied.can_match(plain)
+    book.add(implied)
+    assert book.best_ask == (10.10, 2.0)
+
+    # A buy at 10.05 does not cross the display price, so it rests
+    book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=1.0))
+    assert book.best_bid == (10.05, 1.0)
+
+    # A buy displayed at 10.00 but matching at 10.10 trades at the resting display price
+    taker = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=1.0, matching_price=10.10
+    )
+    fills = book.add(taker)
+    assert len(fills) == 1
+    assert fills[0].price == pytest.approx(10.10)
+    assert fills[0].quantity == 1.0
+
+    # A resting order whose matching price does not cross stops the walk
+    book = lb.OrderBook(tick_size=0.05)
+    book.add(
+        book.create_order(
+            lb.OrderType.Sell, price=10.00, quantity=1.0, matching_price=10.50
+        )
+    )
+    taker = book.create_order(
+        lb.OrderType.Buy, price=10.20, quantity=1.0, time_in_force=lb.TimeInForce.IOC
+    )
+    assert book.add(taker) == []
+    assert book.best_ask == (10.00, 1.0)
+
+    with pytest.raises(lb.OrderRejectedError):
+        book.create_order(
+            lb.OrderType.Buy, price=10.00, quantity=1.0, matching_price=-1.0
+        )
+
+
+def test_crossed_book_policy(order_book: lb.OrderBook):
+    """Test that an add left crossed by matching prices is resolved or rejected."""
+    assert order_book.crossed_book_policy == lb.CrossedBookPolicy.Resolve
+
+    for policy in (lb.CrossedBookPolicy.Resolve, lb.CrossedBookPolicy.Reject):
+        book = lb.OrderBook(tick_size=0.05, crossed_book_policy=policy)
+        # A sell displayed at 10.00 that only matches at 10.50 blocks the walk
+        book.add(
+            book.create_order(
+                lb.OrderType.Sell, price=10.00, quantity=1.0, matching_price=10.50
+            )
+        )
+        book.add(book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0))
+        assert book.best_bid == (9.95, 1.0)
+
+        # The buy cannot trade, but resting it at 10.20 would cross the book
+        crossing = book.create_order(lb.OrderType.Buy, price=10.20, quantity=1.0)
+        if policy == lb.CrossedBookPolicy.Resolve:
+            assert book.add(crossing) == []
+            assert book.stats()["crosses_resolved"] == 1
+        else:
+            with pytest.raises(lb.OrderRejectedError) as excinfo:
+                book.add(crossing)
+            assert excinfo.value.reason == lb.OrderRejectReason.CrossedBook
+            assert book.stats()["orders_added"] == 2
+
+        assert crossing.id not in book
+        assert not book.is_crossed()
+        assert book.best_bid == (9.95, 1.0)
+        assert book.best_ask == (10.00, 1.0)
+
+
+def test_modify_quantity_priority(order_book: lb.OrderBook):
+    """Test that reducing quantity keeps priority and increasing it loses priority."""
+    book = order_book
+
+    first = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    second = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    book.add(first)
+    book.add(second)
+
+    assert book.modify_quantity(first.id, 3.0)
+    assert [order.id for order in book.buy_orders] == [first.id, second.id]
+    assert book.get_order(first.id).quantity == 3.0
+    assert book.buy_orders[0].quantity == 3.0
+
+    assert book.modify_quantity(first.id, 8.0)
+    assert [order.id for order in book.buy_orders] == [second.id, first.id]
+    assert book.get_order(first.id).quantity == 8.0
+
+    assert not book.modify_quantity("non_existent_id", 1.0)
+    with pytest.raises(ValueError):
+        book.modify_quantity(first.id, 0.0)
+
+
+def test_filled_quantity(order_book: lb.OrderBook):
+    """Test that orders track how much of them has filled."""
+    book = order_book
+
+    sell = book.create_order(lb.OrderType.Sell, price=10.00, quantity=4.0)
+    assert sell.filled_quantity == 0.0
+    assert sell.remaining_quantity == 4.0
+    assert sell.fill_ratio == 0.0
+    book.add(sell)
+
+    buy = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    book.add(buy)
+    resting = book.get_order(sell.id)
+    assert resting.status == lb.OrderStatus.Open
+    assert resting.filled_quantity == 1.0
+    assert resting.remaining_quantity == 3.0
+    assert resting.fill_ratio == pytest.approx(0.25)
+
+    filled = book.get_order(buy.id)
+    assert filled.status == lb.OrderStatus.Filled
+    assert filled.filled_quantity == 1.0
+    assert filled.fill_ratio == 1.0
+
+    # Fills accumulate across executions until the order is Filled
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0))
+    resting = book.get_order(sell.id)
+    assert resting.status == lb.OrderStatus.Filled
+    assert resting.filled_quantity == 4.0
+    assert resting.remaining_quantity == 0.0
+    assert resting.fill_ratio == 1.0
+
+    # A reduced order is Filled once its reduced size has traded, short of its
+    # original quantity
+    reduced = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0)
+    book.add(reduced)
+    book.reduce(reduced.id, 2.0)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0))
+    resting = book.get_order(reduced.id)
+    assert resting.status == lb.OrderStatus.Filled
+    assert resting.filled_quantity == 3.0
+    assert resting.original_quantity == 5.0
+    assert resting.remaining_quantity == 0.0
+    assert resting.fill_ratio == 1.0
+
+
+def test_original_quantity(order_book: lb.OrderBook):
+    """Test that the original quantity survives fills, reductions and snapshots."""
+    book = order_book
+
+    sell = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0)
+    assert sell.original_quantity == 5.0
+    book.add(sell)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    book.reduce(sell.id, 1.0)
+
+    resting = book.get_order(sell.id)
+    assert resting.quantity == 2.0
+    assert resting.original_quantity == 5.0
+    restored = lb.OrderBook.from_json(book.to_json())
+    assert restored.get_order(sell.id).original_quantity == 5.0
+
+    iceberg = book.create_iceberg_order(lb.OrderType.Buy, 9.00, 10.0, 2.0)
+    assert iceberg.quantity == 2.0
+    assert iceberg.original_quantity == 10.0
+
+
+def test_reduce(order_book: lb.OrderBook):
+    """Test partially canceling a resting order while keeping its priority."""
+    book = order_book
+
+    first = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0)
+    second = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0)
+    book.add(first)
+    book.add(second)
+
+    reduced = book.reduce(first.id, 2.0)
+    assert reduced.quantity == 3.0
+    assert reduced.status == lb.OrderStatus.Open
+    assert book.get_order(first.id).quantity == 3.0
+    assert book.quantity_at(lb.OrderType.Sell, 10.00) == 8.0
+
+    # The reduced order is still first in line
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert fills[0].sell_id == first.id
+
+    # Reducing by the whole remainder cancels the order
+    canceled = book.reduce(first.id, 2.0)
+    assert canceled.status == lb.OrderStatus.Canceled
+    assert book.get_order(first.id) is None
+    assert book.orders_at(lb.OrderType.Sell, 10.00) == 1
+
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.reduce(second.id, 6.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.ReductionTooLarge
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.reduce(first.id, 1.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.NotResting
+
+    # Reductions are logged and replayed
+    replayed = lb.OrderBook.replay(book.events(), tick_size=0.05)
+    assert replayed.get_order(second.id).quantity == 5.0
+    assert replayed.get_order(first.id) is None
+
+
+def test_reduce_iceberg(order_book: lb.OrderBook):
+    """Test that reducing an iceberg takes from its hidden reserve first."""
+    book = order_book
+    iceberg = book.create_iceberg_order(lb.OrderType.Sell, 10.00, 10.0, 2.0)
+    book.add(iceberg)
+
+    reduced = book.reduce(iceberg.id, 7.0)
+    assert reduced.quantity == 2.0
+    assert reduced.hidden_quantity == 1.0
+
+    reduced = book.reduce(iceberg.id, 2.0)
+    assert reduced.quantity == 1.0
+    assert reduced.hidden_quantity == 0.0
+
+
+def test_reject_quantity_increase(order_book: lb.OrderBook):
+    """Test that the strict mode refuses quantity increases and leaves the order."""
+    book = order_book
+
+    first = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    second = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    book.add(first)
+    book.add(second)
+
+    with pytest.raises(ValueError):
+        book.modify_quantity(first.id, 6.0, reject_increase=True)
+    with pytest.raises(ValueError):
+        book.replace(first.id, 10.05, 6.0, reject_increase=True)
+    assert [order.id for order in book.buy_orders] == [first.id, second.id]
+    assert book.get_order(first.id).quantity == 5.0
+
+    # Decreases are still allowed and keep priority
+    assert book.modify_quantity(first.id, 4.0, reject_increase=True)
+    assert [order.id for order in book.buy_orders] == [first.id, second.id]
+
+
+def test_queue_position(order_book: lb.OrderBook):
+    """Test the index and quantity ahead of a resting order within its level."""
+    book = order_book
+
+    orders = [
+        book.create_order(lb.OrderType.Sell, price=10.00, quantity=quantity)
+        for quantity in (2.0, 3.0, 4.0)
+    ]
+    for order in orders:
+        book.add(order)
+
+    assert book.queue_position(orders[0].id) == (0, 0.0)
+    assert book.queue_position(orders[2].id) == (2, 5.0)
+
+    book.cancel(orders[0].id)
+    assert book.queue_position(orders[2].id) == (1, 3.0)
+    assert book.queue_position(orders[0].id) is None
+    assert book.queue_position("non_existent_id") is None
+
+
+def test_replace_order(order_book: lb.OrderBook):
+    """Test cancel-replace priority rules and crossing replacements."""
+    book = order_book
+
+    first = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    second = book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    book.add(first)
+    book.add(second)
+
+    # A quantity decrease at the same price keeps priority
+    replaced = book.replace(first.id, 10.00, 4.0)
+    assert replaced.id == first.id
+    assert replaced.quantity == 4.0
+    assert [order.id for order in book.buy_orders] == [first.id, second.id]
+
+    # A quantity increase sends the order to the back of the level
+    book.replace(first.id, 10.00, 6.0)
+    assert [order.id for order in book.buy_orders] == [second.id, first.id]
+
+    # A price change that crosses the book matches immediately
+    sell_order = book.create_order(lb.OrderType.Sell, price=10.50, quantity=2.0)
+    book.add(sell_order)
+    replaced = book.replace(sell_order.id, 10.00, 2.0)
+    assert replaced.status == lb.OrderStatus.Filled
+    assert book.get_order(second.id).quantity == 3.0
+
+    with pytest.raises(ValueError):
+        book.replace("non_existent_id", 10.00, 1.0)
+
+
+def test_rejected_replace_keeps_original(order_book: lb.OrderBook):
+    """Test that a replacement rejected by the book's checks leaves the original resting."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.50, quantity=1.0))
+    first = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0, post_only=True)
+    second = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    book.add(first)
+    book.add(second)
+    events = len(book.events())
+
+    # Moving the post-only order through the ask would take liquidity
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.replace(first.id, 10.50, 1.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.PostOnlyWouldMatch
+    assert book.get_order(first.id).status == lb.OrderStatus.Open
+    assert book.order_ids_at(lb.OrderType.Buy, 10.00) == [first.id, second.id]
+    assert len(book.events()) == events
+
+    book.set_order_filter(lambda order: order.quantity <= 2.0)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.replace(second.id, 10.05, 3.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.FilteredOut
+    assert book.order_ids_at(lb.OrderType.Buy, 10.00) == [first.id, second.id]
+    assert book.validate()
+
+
+def test_cancel_many(order_book: lb.OrderBook):
+    """Test that cancel_many cancels known orders and skips unknown IDs."""
+    book = order_book
+
+    orders = [
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0),
+        book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0),
+    ]
+    for order in orders:
+        book.add(order)
+
+    canceled = book.cancel_many([orders[0].id, "non_existent_id", orders[2].id])
+
+    assert canceled == [orders[0].id, orders[2].id]
+    assert [order.id for order in book.buy_orders] == [orders[1].id]
+    assert book.sell_orders == []
+
+
+def test_cancel_all(order_book: lb.OrderBook):
+    """Test that cancel_all clears one side or the whole book."""
+    book = order_book
+
+    buys = [
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0),
+    ]
+    sell = book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0)
+    stop = book.create_stop_order(lb.OrderType.Sell, stop_price=9.00, quantity=1.0)
+    for order in buys + [sell, stop]:
+        book.add(order)
+
+    assert book.cancel_all(lb.OrderType.Buy) == 2
+    assert book.buy_orders == []
+    assert all(book.get_order(order.id) is None for order in buys)
+    assert book.get_order(sell.id) is not None
+
+    assert book.cancel_all() == 2
+    assert book.sell_orders == []
+    assert book.stop_orders == []
+    assert book.get_order(sell.id) is None
+    assert book.get_order(stop.id) is None
+
+
+@pytest.mark.parametrize(
+    "policy, resting_left, incoming_left",
+    [
+        (lb.SelfTradePrevention.CancelNewest, 5.0, None),
+        (lb.SelfTradePrevention.CancelOldest, None, 3.0),
+        (lb.SelfTradePrevention.CancelBoth, None, None),
+        (lb.SelfTradePrevention.DecrementBoth, 2.0, None),
+    ],
+)
+def test_self_trade_prevention(policy, resting_left, incoming_left):
+    """Test each self-trade prevention policy against a same-owner cross."""
+    book = lb.OrderBook(tick_size=0.05, stp_policy=policy)
+    assert book.stp_policy == policy
+
+    resting = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0, owner="a")
+    book.add(resting)
+    incoming = book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0, owner="a")
+    fills = book.add(incoming)
+    assert fills == []
+
+    def remaining(order: lb.Order):
+        found = book.get_order(order.id)
+        return None if found is None else found.quantity
+
+    assert remaining(resting) == resting_left
+    assert remaining(incoming) == incoming_left
+
+    # Every canceled order is reported and no longer tracked by the book
+    canceled_ids = {order.id for order in book.self_trade_canceled}
+    assert canceled_ids == {
+        order.id for order in (resting, incoming) if remaining(order) is None
+    }
+
+
+@pytest.mark.parametrize(
+    "policy",
+    [lb.SelfTradePrevention.CancelOldest, lb.SelfTradePrevention.CancelNewest],
+)
+def test_self_trade_prevention_fok(policy):
+    """Test that a FOK order does not count its owner's resting orders as liquidity."""
+    book = lb.OrderBook(tick_size=0.05, stp_policy=policy)
+    own = book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0, owner="a")
+    book.add(own)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0, owner="b"))
+
+    fok = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=10.0, owner="a", time_in_force=lb.TimeInForce.FOK
+    )
+    assert book.add(fok) == []
+    assert book.trade_count() == 0
+    assert book.best_ask == (10.00, 10.0)
+    assert book.get_order(own.id).status == lb.OrderStatus.Open
+
+
+def test_self_trade_prevention_ignores_other_owners(order_book: lb.OrderBook):
+    """Test that orders from different or missing owners still match."""
+    book = order_book
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0, owner="a"))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+
+    fills = book.add(
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0, owner="b")
+    )
+    assert len(fills) == 2
+    assert book.self_trade_canceled == []
+
+
+def test_self_trade_prevention_group(order_book: lb.OrderBook):
+    """Test that an STP group spans owners and takes priority over the owner."""
+    book = order_book
+
+    desk = book.create_order(
+        lb.OrderType.Sell, price=10.00, quantity=1.0, owner="a", stp_group="desk"
+    )
+    assert desk.stp_group == "desk"
+    book.add(desk)
+
+    # A different account in the same group does not cross the desk's order
+    colleague = book.create_order(
+        lb.OrderType.Buy, price=10.00, quantity=1.0, owner="b", stp_group="desk"
+    )
+    assert book.add(colleague) == []
+    assert [order.id for order in book.self_trade_canceled] == [colleague.id]
+    assert book.get_order(desk.id).quantity == 1.0
+
+    # The same owner outside the group is not a self-trade
+    fills = book.add(
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0, owner="a")
+    )
+    assert len(fills) == 1
+    assert fills[0].sell_id == desk.id
+
+
+def test_pro_rata_matching():
+    """Test that pro-rata matching splits a fill across the level by size."""
+    book = lb.OrderBook(tick_size=0.05, matching_mode=lb.MatchingMode.ProRata)
+    assert book.matching_mode == lb.MatchingMode.ProRata
+
+    small = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    large = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    book.add(small)
+    book.add(large)
+
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    assert [fill.sell_id for fill in fills] == [small.id, large.id]
+    assert [fill.quantity for fill in fills] == [pytest.approx(0.5), pytest.approx(1.5)]
+    assert book.get_order(small.id).quantity == pytest.approx(0.5)
+    assert book.get_order(large.id).quantity == pytest.approx(1.5)
+
+    # A larger order sweeps the level and continues to the next price
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=2.5))
+    assert sum(fill.quantity for fill in fills) == pytest.approx(2.5)
+    assert book.get_order(small.id).status == lb.OrderStatus.Filled
+    assert book.get_order(large.id).status == lb.OrderStatus.Filled
+    assert book.best_ask == (pytest.approx(10.05), pytest.approx(0.5))
+
+
+def test_pro_rata_allocation_sums_exactly():
+    """Test that pro-rata allocations always add up to the traded quantity."""
+    book = lb.OrderBook(matching_mode=lb.MatchingMode.ProRata)
+    for _ in range(3):
+        book.add(book.create_order(lb.OrderType.Buy, price=5.00, quantity=1.0))
+
+    fills = book.add(book.create_order(lb.OrderType.Sell, price=5.00, quantity=1.0))
+    assert len(fills) == 3
+    assert sum(fill.quantity for fill in fills) == 1.0
+    assert book.depth(1)[0] == [(5.00, pytest.approx(2.0))]
+
+
+def test_best_level_sums_all_orders(order_book: lb.OrderBook):
+    """Test that the top-of-book size covers every order at the best price."""
+    book = order_book
+    for quantity in (1.0, 2.0, 3.5):
+        book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=quantity))
+        book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=quantity))
+
+    assert book.best_bid == (pytest.approx(10.00), pytest.approx(6.5))
+    assert book.best_ask == (pytest.approx(10.10), pytest.approx(6.5))
+
+
+def test_json_round_trip():
+    """Test that a book restored from JSON behaves exactly like the original."""
+    book = lb.OrderBook(tick_size=0.05, stp_policy=lb.SelfTradePrevention.CancelOldest)
+    for price, quantity in ((9.90, 1.0), (9.95, 2.0), (9.95, 0.5)):
+        book.add(book.create_order(lb.OrderType.Buy, price=price, quantity=quantity))
+    for price, quantity in ((10.05, 1.5), (10.10, 3.0)):
+        book.add(book.create_order(lb.OrderType.Sell, price=price, quantity=quantity))
+    book.add(book.create_iceberg_order(lb.OrderType.Sell, 10.10, 4.0, 1.0))
+    book.add(book.create_stop_order(lb.OrderType.Buy, 10.20, 1.0))
+
+    data = book.to_json()
+    restored = lb.OrderBook.from_json(data)
+    assert restored.to_json() == data
+    assert restored.tick_size == book.tick_size
+    assert restored.stp_policy == book.stp_policy
+    assert restored.depth(5) == book.depth(5)
+
+    # The same incoming order produces identical fills on both books
+    incoming = book.create_order(lb.OrderType.Buy, price=10.10, quantity=5.0)
+
+    def summarize(fills):
+        return [(f.quantity, f.price, f.buy_id, f.sell_id, f.timestamp) for f in fills]
+
+    assert summarize(restored.add_at(incoming, 42)) == summarize(book.add_at(incoming, 42))
+    assert restored.to_json() == book.to_json()
+
+    with pytest.raises(ValueError):
+        lb.OrderBook.from_json("not json")
+
+
+def test_bytes_round_trip():
+    """Test that a large book survives a binary snapshot and keeps matching identically."""
+    book = lb.OrderBook()
+    for i in range(10_000):
+        side = lb.OrderType.Buy if i % 2 == 0 else lb.OrderType.Sell
+        price = 9.00 - (i % 100) * 0.01 if side == lb.OrderType.Buy else 10.00 + (i % 100) * 0.01
+        book.add(book.create_order(side, price=price, quantity=1.0 + i % 7))
+
+    data = book.to_bytes()
+    assert isinstance(data, bytes)
+    restored = lb.OrderBook.from_bytes(data)
+    assert restored.to_bytes() == data
+    assert len(restored.buy_orders) == len(book.buy_orders) == 5_000
+    assert len(restored.sell_orders) == len(book.sell_orders) == 5_000
+
+    incoming = book.create_order(lb.OrderType.Buy, price=10.05, quantity=500.0)
+
+    def summarize(fills):
+        return [(f.quantity, f.price, f.buy_id, f.sell_id, f.timestamp) for f in fills]
+
+    fills = book.add_at(incoming, 7)
+    assert fills
+    assert summarize(restored.add_at(incoming, 7)) == summarize(fills)
+    assert restored.to_bytes() == book.to_bytes()
+
+    with pytest.raises(ValueError):
+        lb.OrderBook.from_bytes(b"\x00\x01")
+
+
+def test_event_log_replay():
+    """Test that replaying the event log rebuilds an identical book."""
+    book = lb.OrderBook(tick_size=0.05)
+    first = book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0)
+    second = book.create_order(lb.OrderType.Sell, price=10.10, quantity=3.0)
+    expiring = book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0, expires_at=100)
+    book.add(first)
+    book.add(second)
+    book.add(expiring)
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.5))
+    book.modify_quantity(second.id, 2.0)
+    book.replace(first.id, 10.05, 1.0)
+    book.cancel(second.id)
+    book.expire(100)
+
+    events = book.events()
+    assert [type(event) for event in events] == [
+        lb.Event.Add,
+        lb.Event.Add,
+        lb.Event.Add,
+        lb.Event.Add,
+        lb.Event.Modify,
+        lb.Event.Cancel,
+        lb.Event.Add,
+        lb.Event.Cancel,
+        lb.Event.Cancel,
+    ]
+    assert events[0].order.id == first.id
+    assert events[3].timestamp == fills[0].timestamp
+    assert events[4].new_quantity == 2.0
+    assert events[-1].order_id == expiring.id
+
+    # Failed operations leave no trace in the log
+    assert book.cancel("missing") is None
+    assert len(book.events()) == len(events)
+
+    replayed = lb.OrderBook.replay(events, tick_size=0.05)
+    assert replayed.to_json() == book.to_json()
+    assert len(replayed.events()) == len(events)
+
+
+def test_fills_to_csv(order_book: lb.OrderBook):
+    """Test the retained trade history and its CSV export."""
+    book = order_book
+    assert book.trade_count() == 0
+    assert book.fills_to_csv() == "quantity,price,buy_id,sell_id,timestamp\n"
+
+    sell = book.create_order(lb.OrderType.Sell, price=10.01, quantity=1.0)
+    book.add(sell)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.02, quantity=2.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.02, quantity=1.75))
+    fills += book.add(book.create_order(lb.OrderType.Buy, price=10.02, quantity=1.0))
+
+    assert book.trade_count() == 3
+    assert book.total_traded_volume() == pytest.approx(2.75)
+
+    header, *rows = book.fills_to_csv().splitlines()
+    assert header == "quantity,price,buy_id,sell_id,timestamp"
+    assert len(rows) == 3
+    for row, fill in zip(rows, fills):
+        quantity, price, buy_id, sell_id, timestamp = row.split(",")
+        # Floats round-trip exactly
+        assert float(quantity) == fill.quantity
+        assert float(price) == fill.price
+        assert (buy_id, sell_id, int(timestamp)) == (fill.buy_id, fill.sell_id, fill.timestamp)
+    assert rows[0].split(",")[3] == sell.id
+
+    # IDs with commas, quotes or line breaks are quoted so the CSV reads back intact
+    odd_id = 'desk "a", line\n2'
+    book.add(lb.Order.with_id(odd_id, lb.OrderType.Sell, 200, 1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    *_, last = csv.reader(io.StringIO(book.fills_to_csv()))
+    assert last[3] == odd_id
+
+
+def test_fills_to_columns(order_book: lb.OrderBook):
+    """Test that the columnar fill export matches the Fill objects."""
+    book = order_book
+    assert book.fills_to_columns() == ([], [], [], [], [])
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=2.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=2.5))
+
+    quantities, prices, buy_ids, sell_ids, timestamps = book.fills_to_columns()
+    assert quantities == [fill.quantity for fill in fills]
+    assert prices == [fill.price for fill in fills]
+    assert buy_ids == [fill.buy_id for fill in fills]
+    assert sell_ids == [fill.sell_id for fill in fills]
+    assert timestamps == [fill.timestamp for fill in fills]
+
+
+def test_fill_sequence(order_book: lb.OrderBook):
+    """Test that fills are numbered in execution order, even with equal timestamps."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0))
+
+    buy = book.create_order(lb.OrderType.Buy, price=10.05, quantity=2.0)
+    first = book.add_at(buy, 5)
+    buy = book.create_order(lb.OrderType.Buy, price=10.10, quantity=1.0)
+    second = book.add_at(buy, 5)
+    assert [fill.sequence for fill in first + second] == [1, 2, 3]
+    assert [fill.timestamp for fill in first + second] == [5, 5, 5]
+    assert "#3" in repr(second[0])
+
+    # The counter survives snapshots and does not restart after a drain
+    book.drain_fills()
+    restored = lb.OrderBook.from_json(book.to_json())
+    restored.add(restored.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    fills = restored.add(
+        restored.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    )
+    assert fills[0].sequence == 4
+
+
+def test_drain_fills(order_book: lb.OrderBook):
+    """Test that draining fills empties the history but keeps the book."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    fills += book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+
+    drained = book.drain_fills()
+    assert [fill.buy_id for fill in drained] == [fill.buy_id for fill in fills]
+    assert book.trade_count() == 0
+    assert book.drain_fills() == []
+
+    # Resting orders and the last trade survive the drain
+    assert book.best_ask == (10.00, 1.0)
+    assert book.best_bid == (9.95, 1.0)
+    assert book.last_trade_price == pytest.approx(10.00)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert book.trade_count() == 1
+
+
+def test_fills_for_order(order_book: lb.OrderBook):
+    """Test tracing every partial fill of a single order."""
+    book = order_book
+
+    sell = book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0)
+    book.add(sell)
+    first = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    second = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+
+    trail = book.fills_for_order(sell.id)
+    assert [(fill.buy_id, fill.quantity) for fill in trail] == [
+        (first[0].buy_id, 1.0),
+        (second[0].buy_id, 2.0),
+    ]
+    assert book.fills_for_order(second[0].buy_id)[0].sell_id == sell.id
+    assert book.fills_for_order("non_existent_id") == []
+
+
+def test_last_trade(order_book: lb.OrderBook):
+    """Test that the most recent fill is exposed after each add."""
+    book = order_book
+    assert book.last_trade_price is None
+    assert book.last_trade_quantity is None
+    assert book.last_trade_timestamp is None
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    book.add_at(book.create_order(lb.OrderType.Buy, price=10.05, quantity=1.5), 123)
+
+    assert book.last_trade_price == pytest.approx(10.05)
+    assert book.last_trade_quantity == pytest.approx(0.5)
+    assert book.last_trade_timestamp == 123
+
+    # Orders that do not trade leave the last trade unchanged
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+    assert book.last_trade_price == pytest.approx(10.05)
+
+
+def test_len_and_contains(order_book: lb.OrderBook):
+    """Test that len() and `in` reflect the resting orders only."""
+    book = order_book
+    assert len(book) == 0
+
+    bid = book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0)
+    ask = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    book.add(bid)
+    book.add(ask)
+    assert len(book) == 2
+    assert bid.id in book and ask.id in book
+
+    taker = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    book.add(taker)
+    assert len(book) == 1
+    assert ask.id not in book
+    assert taker.id not in book
+    assert "missing" not in book
+
+
+def test_top_orders(order_book: lb.OrderBook):
+    """Test that top_orders() returns the first n orders in price-time priority."""
+    book = order_book
+    bids = [
+        book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.90, quantity=2.0),
+    ]
+    for order in bids:
+        book.add(order)
+
+    assert [o.id for o in book.top_orders(lb.OrderType.Buy, 2)] == [bids[1].id, bids[0].id]
+    assert len(book.top_orders(lb.OrderType.Buy, 5)) == 3
+    assert book.top_orders(lb.OrderType.Buy, 0) == []
+    assert book.top_orders(lb.OrderType.Sell, 5) == []
+
+
+def test_iter_side(order_book: lb.OrderBook):
+    """Test lazy iteration over one side of the book in price-time priority."""
+    book = order_book
+    bids = [
+        book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0),
+        book.create_order(lb.OrderType.Buy, price=9.90, quantity=2.0),
+    ]
+    asks = [
+        book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0),
+        book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0),
+    ]
+    for order in bids + asks:
+        book.add(order)
+
+    assert [o.id for o in book.iter_side(lb.OrderType.Buy)] == [
+        bids[1].id,
+        bids[0].id,
+        bids[2].id,
+    ]
+    assert [o.id for o in book.iter_side(lb.OrderType.Sell)] == [asks[1].id, asks[0].id]
+
+    # The iterator walks the live book
+    it = book.iter_side(lb.OrderType.Sell)
+    assert next(it).id == asks[1].id
+    book.cancel(asks[0].id)
+    assert list(it) == []
+    assert list(lb.OrderBook().iter_side(lb.OrderType.Buy)) == []
+
+    # Canceling each order as it is yielded skips none of the others
+    seen = []
+    for order in book.iter_side(lb.OrderType.Buy):
+        seen.append(order.id)
+        book.cancel(order.id)
+    assert seen == [bids[1].id, bids[0].id, bids[2].id]
+    assert book.buy_orders == []
+
+
+def test_clear():
+    """Test that clear() resets the book but keeps its configuration."""
+    book = lb.OrderBook(tick_size=0.05, matching_mode=lb.MatchingMode.ProRata)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_stop_order(lb.OrderType.Buy, 11.00, 1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+
+    assert book.clear() is None
+    assert len(book) == 0
+    assert book.stop_orders == []
+    assert book.trade_count() == 0
+    assert book.last_trade_price is None
+    assert book.events() == []
+    assert book.tick_size == 0.05
+    assert book.matching_mode == lb.MatchingMode.ProRata
+    assert book.to_json() == lb.OrderBook(
+        tick_size=0.05, matching_mode=lb.MatchingMode.ProRata
+    ).to_json()
+
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert fills == []
+    assert book.best_bid == (pytest.approx(10.00), 1.0)
+
+
+def test_repr_uses_float_tick_size():
+    """Test that the repr converts ticks to prices without truncating the tick size."""
+    book = lb.OrderBook(tick_size=0.01)
+    book.add(book.create_order(lb.OrderType.Buy, price=1.00, quantity=2.0))
+    assert book.buy_orders[0].price_in_ticks == 100
+
+    assert repr(book).startswith("Best Bid: 2.00 @ 1.00, Best Ask: None")
+
+
+def test_create_order_strict():
+    """Test that strict order creation rejects prices off the tick grid."""
+    book = lb.OrderBook(tick_size=0.01)
+
+    # By default the price silently snaps to the nearest tick
+    assert book.create_order(lb.OrderType.Buy, price=1.005, quantity=1.0).price_in_ticks == 100
+
+    with pytest.raises(ValueError):
+        book.create_order(lb.OrderType.Buy, price=1.005, quantity=1.0, strict=True)
+
+    # Exact multiples pass despite float representation error
+    order = book.create_order(lb.OrderType.Buy, price=1.07, quantity=1.0, strict=True)
+    assert order.price_in_ticks == 107
+    coarse = lb.OrderBook(tick_size=0.05)
+    assert coarse.create_order(lb.OrderType.Sell, 10.05, 1.0, strict=True).price_in_ticks == 201
+
+
+def test_create_order_rounding(order_book: lb.OrderBook):
+    """Test each rounding mode for prices that fall between ticks."""
+    book = order_book
+
+    def ticks(side, price, rounding):
+        return book.create_order(side, price, 1.0, rounding=rounding).price_in_ticks
+
+    assert ticks(lb.OrderType.Buy, 10.02, lb.RoundingMode.Nearest) == 200
+    assert ticks(lb.OrderType.Buy, 10.02, lb.RoundingMode.Up) == 201
+    assert ticks(lb.OrderType.Sell, 10.04, lb.RoundingMode.Down) == 200
+
+    # Conservative rounding never makes an order more aggressive
+    assert ticks(lb.OrderType.Buy, 10.04, lb.RoundingMode.TowardConservative) == 200
+    assert ticks(lb.OrderType.Sell, 10.01, lb.RoundingMode.TowardConservative) == 201
+
+    # Prices already on the grid are unaffected by float noise
+    assert ticks(lb.OrderType.Buy, 10.10, lb.RoundingMode.Up) == 202
+    assert ticks(lb.OrderType.Sell, 10.10, lb.RoundingMode.Down) == 202
+
+
+def test_add_many(order_book: lb.OrderBook):
+    """Test that add_many matches each order against the book left by the previous one."""
+    book = order_book
+    sell = book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0)
+    first = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.5)
+    second = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+
+    results = book.add_many([sell, first, second])
+    assert [[fill.quantity for fill in fills] for fills in results] == [[], [1.5], [0.5]]
+    assert book.best_bid == (pytest.approx(10.00), pytest.approx(0.5))
+    assert book.add_many([]) == []
+
+    # A rejected order stops the batch, keeping the orders before it
+    resting = book.create_order(lb.OrderType.Sell, price=10.50, quantity=1.0)
+    post_only = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0, post_only=True)
+    skipped = book.create_order(lb.OrderType.Sell, price=10.55, quantity=1.0)
+    with pytest.raises(ValueError):
+        book.add_many([resting, post_only, skipped])
+    assert resting.id in book
+    assert skipped.id not in book
+
+
+def test_merge(order_book: lb.OrderBook):
+    """Test that merging books re-matches crossing orders and preserves IDs."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0))
+
+    other = lb.OrderBook(tick_size=0.05)
+    crossing = other.create_order(lb.OrderType.Buy, price=10.05, quantity=3.0)
+    resting = other.create_order(lb.OrderType.Sell, price=10.50, quantity=2.0)
+    other.add(crossing)
+    other.add(resting)
+
+    fills = book.merge(other)
+    assert [(fill.buy_id, fill.quantity) for fill in fills] == [(crossing.id, 1.0)]
+    assert book.get_order(crossing.id).quantity == 2.0
+    assert book.get_order(resting.id).quantity == 2.0
+    assert len(other) == 2  # The merged book is left unchanged
+    assert book.validate()
+
+    with pytest.raises(ValueError, match="already tracked"):
+        book.merge(other)
+    with pytest.raises(ValueError, match="tick size"):
+        book.merge(lb.OrderBook(tick_size=0.01))
+
+
+def test_seed(order_book: lb.OrderBook):
+    """Test that seeding places orders directly and refuses to cross the book."""
+    book = order_book
+    first = book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0)
+    second = book.create_order(lb.OrderType.Buy, price=9.95, quantity=2.0)
+    ask = book.create_order(lb.OrderType.Sell, price=10.05, quantity=3.0)
+    book.seed([first, second, ask])
+
+    assert book.trade_count() == 0
+    assert book.order_ids_at(lb.OrderType.Buy, 9.95) == [first.id, second.id]
+    assert book.get_order(first.id).timestamp == first.timestamp
+    assert book.best_ask == (10.05, 3.0)
+    assert book.validate()
+
+    # Nothing is placed if any order would lock or cross the book
+    locking = book.create_order(lb.OrderType.Sell, price=9.95, quantity=1.0)
+    extra = book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.seed([extra, locking])
+    assert excinfo.value.reason == lb.OrderRejectReason.CrossedBook
+    assert extra.id not in book
+
+    with pytest.raises(lb.OrderRejectedError):
+        book.seed([ask])
+    with pytest.raises(ValueError, match="cannot be seeded"):
+        book.seed([book.create_market_order(lb.OrderType.Buy, 1.0)])
+
+    # Only open GTC limit orders at a valid price can be seeded
+    canceled = book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0)
+    book.add(canceled)
+    canceled = book.cancel(canceled.id)
+    unseedable = [
+        book.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0),
+        book.create_order(lb.OrderType.Buy, 9.90, 1.0, time_in_force=lb.TimeInForce.IOC),
+        canceled,
+    ]
+    for order in unseedable:
+        with pytest.raises(lb.OrderRejectedError) as excinfo:
+            book.seed([order])
+        assert excinfo.value.reason == lb.OrderRejectReason.NotRestable
+    spreads = lb.OrderBook(tick_size=0.05, allow_negative_prices=True)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.seed([spreads.create_order(lb.OrderType.Buy, price=-0.25, quantity=1.0)])
+    assert excinfo.value.reason == lb.OrderRejectReason.NonPositivePrice
+
+    banded = lb.OrderBook(tick_size=0.05, price_band_ticks=2)
+    banded.add(banded.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    banded.add(banded.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    with pytest.raises(lb.PriceBandError):
+        banded.seed([banded.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0)])
+    assert book.validate()
+
+
+def test_simulate(order_book: lb.OrderBook):
+    """Test that simulate predicts add's fills without touching the book."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_iceberg_order(lb.OrderType.Sell, 10.05, 3.0, 1.0))
+    before = book.to_json()
+
+    incoming = book.create_order(lb.OrderType.Buy, price=10.05, quantity=2.5)
+    simulated = book.simulate(incoming)
+    assert book.to_json() == before
+
+    def summarize(fills):
+        return [(f.quantity, f.price, f.buy_id, f.sell_id) for f in fills]
+
+    assert summarize(simulated) == summarize(book.add(incoming))
+    assert [fill.price for fill in simulated] == [10.00, 10.05, 10.05]
+
+    with pytest.raises(ValueError):
+        book.simulate(
+            book.create_order(lb.OrderType.Buy, price=10.05, quantity=1.0, post_only=True)
+        )
+
+
+def test_on_fill_callback(order_book: lb.OrderBook):
+    """Test that the fill callback sees every fill and that its exceptions propagate."""
+    book = order_book
+    received = []
+    book.set_on_fill(received.append)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.05, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=1.5))
+    assert [(f.quantity, f.price) for f in received] == [(f.quantity, f.price) for f in fills]
+
+    # Simulations do not notify, and a removed callback is no longer called
+    book.simulate(book.create_order(lb.OrderType.Buy, price=10.05, quantity=0.5))
+    book.set_on_fill(None)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=0.25))
+    assert len(received) == 2
+
+    # The callback can read the book, which is already fully updated
+    asks = []
+    book.set_on_fill(lambda fill: asks.append(book.best_ask))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=0.125))
+    assert asks == [(10.05, 0.125)]
+
+    def fail(fill):
+        raise RuntimeError("callback failed")
+
+    book.set_on_fill(fail)
+    with pytest.raises(RuntimeError, match="callback failed"):
+        book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=0.25))
+    assert book.sell_orders == []  # The order was still applied
+
+
+def test_order_filter(order_book: lb.OrderBook):
+    """Test that the order filter vetoes orders and that its exceptions propagate."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=5.0))
+    book.set_order_filter(lambda order: order.quantity <= 2.0)
+
+    fat_finger = book.create_order(lb.OrderType.Buy, price=10.00, quantity=3.0)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.add(fat_finger)
+    assert excinfo.value.reason == lb.OrderRejectReason.FilteredOut
+    assert fat_finger.id not in book
+    assert book.best_ask == (10.00, 5.0)
+
+    assert len(book.add(book.create_order(lb.OrderType.Buy, 10.00, 2.0))) == 1
+
+    def fail(order):
+        raise RuntimeError("filter failed")
+
+    book.set_order_filter(fail)
+    with pytest.raises(RuntimeError, match="filter failed"):
+        book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert book.best_ask == (10.00, 3.0)  # The order was not applied
+
+    book.set_order_filter(None)
+    assert len(book.add(book.create_order(lb.OrderType.Buy, 10.00, 3.0))) == 1
+
+    # The filter can read the book, and vets every all-or-none leg before any commits
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=2.0))
+    def above_bid(order):
+        return book.ticks_to_price(order.price_in_ticks) > book.best_bid[0]
+
+    book.set_order_filter(lambda order: order.side == lb.OrderType.Buy or above_bid(order))
+    legs = [
+        book.create_order(lb.OrderType.Buy, 10.00, 2.0, time_in_force=lb.TimeInForce.IOC),
+        book.create_order(lb.OrderType.Sell, 9.00, 1.0, time_in_force=lb.TimeInForce.IOC),
+    ]
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        book.add_group(legs, all_or_none=True)
+    assert excinfo.value.reason == lb.OrderRejectReason.FilteredOut
+    assert book.best_ask == (10.00, 2.0)
+    assert book.best_bid == (9.00, 2.0)
+
+
+@pytest.mark.parametrize(
+    "rule, aggressor, expected",
+    [
+        (lb.FillPriceRule.Seller, lb.OrderType.Buy, 10.00),
+        (lb.FillPriceRule.Seller, lb.OrderType.Sell, 10.00),
+        (lb.FillPriceRule.Maker, lb.OrderType.Buy, 10.00),
+        (lb.FillPriceRule.Maker, lb.OrderType.Sell, 10.20),
+        (lb.FillPriceRule.Taker, lb.OrderType.Buy, 10.20),
+        (lb.FillPriceRule.Taker, lb.OrderType.Sell, 10.00),
+        (lb.FillPriceRule.Midpoint, lb.OrderType.Buy, 10.10),
+        (lb.FillPriceRule.Midpoint, lb.OrderType.Sell, 10.10),
+    ],
+)
+def test_fill_price_rule(rule, aggressor, expected):
+    """Test each fill price rule with both a buy and a sell aggressor."""
+    book = lb.OrderBook(tick_size=0.05, fill_price_rule=rule)
+    assert book.fill_price_rule == rule
+
+    # The aggressor crosses a resting order priced 0.20 through it
+    maker_side, maker_price, taker_price = (
+        (lb.OrderType.Sell, 10.00, 10.20)
+        if aggressor == lb.OrderType.Buy
+        else (lb.OrderType.Buy, 10.20, 10.00)
+    )
+    book.add(book.create_order(maker_side, price=maker_price, quantity=1.0))
+    fills = book.add(book.create_order(aggressor, price=taker_price, quantity=1.0))
+    assert fills[0].price == pytest.approx(expected)
+
+
+def test_fill_price_rule_midpoint_rounding():
+    """Test that an off-grid midpoint rounds to the nearest tick and market orders pay the maker price."""
+    book = lb.OrderBook(tick_size=0.05, fill_price_rule=lb.FillPriceRule.Midpoint)
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=1.0))
+    assert fills[0].price == pytest.approx(10.05)  # 200.5 ticks rounds up to 201
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.50, quantity=1.0))
+    fills = book.add(book.create_market_order(lb.OrderType.Buy, 1.0))
+    assert fills[0].price == pytest.approx(10.50)
+
+
+@pytest.mark.parametrize(
+    "rule, aggressor, expected",
+    [
+        (lb.FillPriceRule.Seller, lb.OrderType.Buy, 0.20),
+        (lb.FillPriceRule.Seller, lb.OrderType.Sell, 0.0),
+        (lb.FillPriceRule.Maker, lb.OrderType.Buy, 0.20),
+        (lb.FillPriceRule.Maker, lb.OrderType.Sell, 0.20),
+        (lb.FillPriceRule.Taker, lb.OrderType.Buy, 0.0),
+        (lb.FillPriceRule.Taker, lb.OrderType.Sell, 0.0),
+        (lb.FillPriceRule.Midpoint, lb.OrderType.Buy, 0.10),
+        (lb.FillPriceRule.Midpoint, lb.OrderType.Sell, 0.10),
+    ],
+)
+def test_price_improvement(rule, aggressor, expected):
+    """Test that fills report how much better than its limit the taker traded."""
+    book = lb.OrderBook(tick_size=0.05, fill_price_rule=rule)
+
+    # The aggressor crosses a resting order priced 0.20 through it
+    maker_side, maker_price, taker_price = (
+        (lb.OrderType.Sell, 10.00, 10.20)
+        if aggressor == lb.OrderType.Buy
+        else (lb.OrderType.Buy, 10.20, 10.00)
+    )
+    book.add(book.create_order(maker_side, price=maker_price, quantity=2.0))
+    fills = book.add(book.create_order(aggressor, price=taker_price, quantity=1.0))
+    assert fills[0].price_improvement == pytest.approx(expected)
+    assert fills[0].price_improvement >= 0.0
+
+    # Matching exactly at the limit, or without a limit, is no improvement
+    fills = book.add(book.create_order(aggressor, price=maker_price, quantity=0.5))
+    assert fills[0].price_improvement == 0.0
+    fills = book.add(book.create_market_order(aggressor, 0.5))
+    assert fills[0].price_improvement == 0.0
+
+
+@pytest.mark.parametrize(
+    "priority, expected",
+    [
+        (lb.LevelPriority.Time, [1.0, 5.0, 3.0]),
+        (lb.LevelPriority.Size, [5.0, 3.0, 1.0]),
+    ],
+)
+def test_level_priority(priority, expected):
+    """Test that the level priority decides which resting order at a price matches first."""
+    book = lb.OrderBook(tick_size=0.05, level_priority=priority)
+    assert book.level_priority == priority
+    for quantity in [1.0, 5.0, 3.0]:
+        book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=quantity))
+
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=9.0))
+    assert [fill.quantity for fill in fills] == expected
+    assert book.validate()
+
+
+def test_level_priority_random_replay():
+    """Test that random level priority fills every order and is reproduced by replay."""
+    book = lb.OrderBook(tick_size=0.05, level_priority=lb.LevelPriority.Random)
+    for _ in range(20):
+        book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=20.0))
+    assert len({fill.sell_id for fill in fills}) == 20
+    assert book.sell_orders == []
+
+    replayed = lb.OrderBook.replay(
+        book.events(), tick_size=0.05, level_priority=lb.LevelPriority.Random
+    )
+    assert replayed.to_json() == book.to_json()
+
+
+def test_peg_orders(order_book: lb.OrderBook):
+    """Test that pegged orders are placed at and follow their reference price."""
+    book = order_book
+    with pytest.raises(ValueError, match="unavailable"):
+        book.add(book.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0))
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    ask = book.create_order(lb.OrderType.Sell, price=10.50, quantity=1.0)
+    book.add(ask)
+    joiner = book.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0, 1)
+    mid = book.create_peg_order(lb.OrderType.Sell, lb.PegReference.Mid, 1.0)
+    aggressive = book.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0, 3)
+    outside = book.create_peg_order(lb.OrderType.Sell, lb.PegReference.BestAsk, 1.0, 1)
+    for order in [joiner, mid, aggressive, outside]:
+        book.add(order)
+
+    # Prices are in ticks of 0.05, and pegs never follow other pegs
+    def peg_prices():
+        return [book.get_order(order.id).price_in_ticks for order in [joiner, aggressive, outside]]
+
+    assert peg_prices() == [201, 203, 211]
+    assert book.get_order(mid.id).price_in_ticks == 205
+    assert book.get_order(mid.id).peg_reference == lb.PegReference.Mid
+
+    # The market moves up; the mid peg reprices to 204 and the aggressive peg crosses it
+    second_ask = book.create_order(lb.OrderType.Sell, price=10.30, quantity=1.0)
+    book.add(second_ask)
+    book.add(book.create_order(lb.OrderType.Buy, price=10.10, quantity=1.0))
+    fills = book.reprice_pegs()
+    assert [(fill.buy_id, fill.sell_id) for fill in fills] == [(aggressive.id, mid.id)]
+    assert fills[0].price == pytest.approx(10.20)
+    assert peg_prices()[0] == 203
+    assert peg_prices()[2] == 207
+    assert book.validate()
+
+    replayed = lb.OrderBook.replay(book.events(), tick_size=0.05)
+    assert replayed.to_json() == book.to_json()
+
+    # A peg whose reference side is empty stays where it is
+    book.cancel(ask.id)
+    book.cancel(second_ask.id)
+    assert book.reprice_pegs() == []
+    assert book.get_order(outside.id).price_in_ticks == 207
+
+
+def test_peg_reprice_respects_price_checks():
+    """Test that a peg stays put when its new price is outside the band or off the multiple."""
+    banded = lb.OrderBook(tick_size=1.0, price_band_ticks=5)
+    banded.add(banded.create_order(lb.OrderType.Sell, price=100.0, quantity=1.0))
+    banded.add(banded.create_order(lb.OrderType.Buy, price=100.0, quantity=1.0))
+    bid = banded.create_order(lb.OrderType.Buy, price=100.0, quantity=1.0)
+    banded.add(bid)
+    peg = banded.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0, -4)
+    banded.add(peg)
+    assert banded.get_order(peg.id).price_in_ticks == 96
+
+    # 94 is more than 5 ticks from the last trade at 100, so the peg stays at 96
+    banded.add(banded.create_order(lb.OrderType.Buy, price=98.0, quantity=1.0))
+    banded.cancel(bid.id)
+    assert banded.reprice_pegs() == []
+    assert banded.get_order(peg.id).price_in_ticks == 96
+    banded.add(banded.create_order(lb.OrderType.Buy, price=99.0, quantity=1.0))
+    banded.reprice_pegs()
+    assert banded.get_order(peg.id).price_in_ticks == 95
+
+    # A mid peg between 100 and 115 would sit at 107, off the multiple of 5
+    coarse = lb.OrderBook(tick_size=1.0, tick_multiple=5)
+    coarse.add(coarse.create_order(lb.OrderType.Buy, price=100.0, quantity=1.0))
+    ask = coarse.create_order(lb.OrderType.Sell, price=110.0, quantity=1.0)
+    coarse.add(ask)
+    mid = coarse.create_peg_order(lb.OrderType.Buy, lb.PegReference.Mid, 1.0)
+    coarse.add(mid)
+    coarse.add(coarse.create_order(lb.OrderType.Sell, price=115.0, quantity=1.0))
+    coarse.cancel(ask.id)
+    coarse.reprice_pegs()
+    assert coarse.get_order(mid.id).price_in_ticks == 105
+    assert coarse.validate()
+
+
+@pytest.mark.parametrize(
+    "policy", [lb.CrossedBookPolicy.Resolve, lb.CrossedBookPolicy.Reject]
+)
+def test_peg_reprice_crossed_book_policy(policy):
+    """Test that a peg repriced through an ask it cannot trade with never leaves the book crossed."""
+    book = lb.OrderBook(tick_size=1.0, crossed_book_policy=policy)
+    book.add(book.create_order(lb.OrderType.Buy, price=95.0, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=100.0, quantity=1.0, matching_price=102.0))
+    peg = book.create_peg_order(lb.OrderType.Buy, lb.PegReference.BestBid, 1.0, 3)
+    book.add(peg)
+    assert book.get_order(peg.id).price_in_ticks == 98
+
+    # The bid moves to 97, so the peg would display at 100 against an ask matching at 102
+    book.add(book.create_order(lb.OrderType.Buy, price=97.0, quantity=1.0))
+    assert book.reprice_pegs() == []
+    if policy == lb.CrossedBookPolicy.Resolve:
+        assert peg.id not in book
+        assert book.stats()["crosses_resolved"] == 1
+    else:
+        assert book.get_order(peg.id).price_in_ticks == 98
+    assert not book.is_crossed()
+    assert book.validate()
+
+    replayed = lb.OrderBook.replay(book.events(), tick_size=1.0, crossed_book_policy=policy)
+    assert replayed.to_json() == book.to_json()
+
+
+def test_auction_uncross(order_book: lb.OrderBook):
+    """Test that an auction collects orders and crosses them at one clearing price."""
+    book = order_book
+    book.start_auction()
+    assert book.in_auction
+    with pytest.raises(ValueError, match="auction"):
+        book.add(book.create_market_order(lb.OrderType.Buy, 1.0))
+
+    for side, price, quantity in [
+        (lb.OrderType.Buy, 10.20, 3.0),
+        (lb.OrderType.Buy, 10.10, 2.0),
+        (lb.OrderType.Buy, 10.00, 4.0),
+        (lb.OrderType.Sell, 9.90, 2.0),
+        (lb.OrderType.Sell, 10.00, 2.0),
+        (lb.OrderType.Sell, 10.10, 3.0),
+        (lb.OrderType.Sell, 10.30, 1.0),
+    ]:
+        assert book.add(book.create_order(side, price=price, quantity=quantity)) == []
+    assert book.best_bid[0] > book.best_ask[0]
+
+    # 10.10 executes 5 units, more than any other price
+    price, fills = book.uncross()
+    assert price == pytest.approx(10.10)
+    assert [fill.quantity for fill in fills] == [2.0, 1.0, 1.0, 1.0]
+    assert all(fill.price == pytest.approx(10.10) for fill in fills)
+    assert not book.in_auction
+    assert book.best_bid == (10.00, 4.0)
+    assert book.best_ask[1] == 2.0
+    assert book.validate()
+
+    replayed = lb.OrderBook.replay(book.events(), tick_size=0.05)
+    assert replayed.to_json() == book.to_json()
+    assert book.uncross() == (None, [])
+
+
+def test_auction_clearing_price_ties(order_book: lb.OrderBook):
+    """Test that equal-volume clearing prices fall back to the last trade, then the lowest."""
+    book = order_book
+    book.start_auction()
+    book.add(book.create_order(lb.OrderType.Buy, price=10.10, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    assert book.uncross()[0] == pytest.approx(10.00)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.30, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.30, quantity=1.0))
+
+    book.start_auction()
+    book.add(book.create_order(lb.OrderType.Buy, price=10.20, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    assert book.uncross()[0] == pytest.approx(10.20)  # Closest to the last trade at 10.30
+
+
+def test_auction_uses_matching_prices():
+    """Test that an auction clears on matching prices rather than display prices."""
+    book = lb.OrderBook(tick_size=1.0)
+    book.start_auction()
+    buy = book.create_order(lb.OrderType.Buy, price=100.0, quantity=1.0, matching_price=90.0)
+    book.add(buy)
+    book.add(book.create_order(lb.OrderType.Sell, price=95.0, quantity=1.0))
+
+    # The bid only displays above the ask, so nothing crosses
+    assert book.uncross() == (None, [])
+    assert book.get_order(buy.id).status == lb.OrderStatus.Open
+
+    # A bid displayed below the ask still trades if it matches through it
+    book.start_auction()
+    hidden = book.create_order(lb.OrderType.Buy, price=90.0, quantity=1.0, matching_price=96.0)
+    book.add(hidden)
+    price, fills = book.uncross()
+    assert price == 95.0
+    assert [(fill.buy_id, fill.price) for fill in fills] == [(hidden.id, 95.0)]
+    assert book.validate()
+
+
+def test_fees():
+    """Test that fills carry maker and taker fees, including a maker rebate."""
+    book = lb.OrderBook(tick_size=0.5, maker_fee_bps=-1.0, taker_fee_bps=5.0)
+    assert (book.maker_fee_bps, book.taker_fee_bps) == (-1.0, 5.0)
+    book.add(book.create_order(lb.OrderType.Sell, price=100.0, quantity=2.0))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=100.0, quantity=2.0))
+
+    # The notional is 200, so the maker earns 0.02 and the taker pays 0.10
+    assert fills[0].maker_fee == pytest.approx(-0.02)
+    assert fills[0].taker_fee == pytest.approx(0.10)
+
+    free = lb.OrderBook(tick_size=0.5)
+    free.add(free.create_order(lb.OrderType.Sell, price=100.0, quantity=2.0))
+    fill = free.add(free.create_order(lb.OrderType.Buy, price=100.0, quantity=2.0))[0]
+    assert (fill.maker_fee, fill.taker_fee) == (0.0, 0.0)
+
+    with pytest.raises(ValueError):
+        lb.OrderBook(taker_fee_bps=float("nan"))
+
+
+def test_order_size_limits():
+    """Test that the book enforces minimum, maximum and lot size constraints."""
+    book = lb.OrderBook(tick_size=0.05, min_quantity=1.0, max_quantity=100.0, lot_size=0.5)
+    assert (book.min_quantity, book.max_quantity, book.lot_size) == (1.0, 100.0, 0.5)
+
+    with pytest.raises(ValueError, match="minimum"):
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=0.5)
+    with pytest.raises(ValueError, match="maximum"):
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=100.5)
+    with pytest.raises(ValueError, match="lot size"):
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.25)
+
+    order = book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.5)
+    book.add(order)
+    with pytest.raises(ValueError):
+        book.modify_quantity(order.id, 2.2)
+
+    # Orders built outside the book are checked when added
+    with pytest.raises(ValueError):
+        book.add(lb.Order(lb.OrderType.Buy, 200, 0.5))
+
+    # Resizing an iceberg is checked against its total size, hidden reserve included
+    capped = lb.OrderBook(tick_size=0.05, max_quantity=10.0)
+    iceberg = capped.create_iceberg_order(
+        lb.OrderType.Sell, price=10.00, quantity=10.0, display_quantity=2.0
+    )
+    capped.add(iceberg)
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        capped.modify_quantity(iceberg.id, 9.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.AboveMaxSize
+    assert capped.get_order(iceberg.id).quantity == 2.0
+    assert capped.modify_quantity(iceberg.id, 1.0)
+
+    # Books without limits accept any positive quantity
+    unconstrained = lb.OrderBook()
+    assert unconstrained.min_quantity is None
+    unconstrained.create_order(lb.OrderType.Buy, price=10.00, quantity=0.001)
+
+
+def test_integer_quantities():
+    """Test that an integer book rejects fractional quantities and allocates whole units."""
+    book = lb.OrderBook(
+        tick_size=0.05, matching_mode=lb.MatchingMode.ProRata, quantity_is_integer=True
+    )
+    assert book.quantity_is_integer
+
+    with pytest.raises(ValueError, match="whole number"):
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.5)
+    with pytest.raises(ValueError, match="whole number"):
+        book.create_iceberg_order(lb.OrderType.Sell, 10.00, 3.0, 1.5)
+    order = book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0)
+    book.add(order)
+    with pytest.raises(ValueError, match="whole number"):
+        book.modify_quantity(order.id, 1.5)
+    book.cancel(order.id)
+
+    # A pro-rata split of 5 across 3/3/3/1 cannot be exact, yet every fill is whole
+    for quantity in [3.0, 3.0, 3.0, 1.0]:
+        book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=quantity))
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0))
+    assert sum(fill.quantity for fill in fills) == 5.0
+    assert all(fill.quantity == int(fill.quantity) for fill in fills)
+    assert all(order.quantity == int(order.quantity) for order in book.sell_orders)
+    assert book.validate()
+
+
+def test_price_band():
+    """Test that limit orders far from the last trade are rejected with a distinct error."""
+    book = lb.OrderBook(tick_size=0.05, price_band_ticks=4)
+    assert book.price_band_ticks == 4
+
+    # Without a last trade there is no reference price, so nothing is rejected
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert book.last_trade_price == pytest.approx(10.00)
+
+    book.add(book.create_order(lb.OrderType.Buy, price=9.80, quantity=1.0))  # 4 ticks away
+    far = book.create_order(lb.OrderType.Buy, price=9.75, quantity=1.0)
+    with pytest.raises(lb.PriceBandError):
+        book.add(far)
+    assert far.id not in book
+    assert issubclass(lb.PriceBandError, ValueError)
+
+    # Market orders carry no price and are never banded
+    fills = book.add(book.create_market_order(lb.OrderType.Buy, 1.0))
+    assert len(fills) == 1
+
+
+def test_quantity_and_orders_at(order_book: lb.OrderBook):
+    """Test inspecting a single price level."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.5))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=4.0))
+
+    assert book.quantity_at(lb.OrderType.Buy, 10.00) == pytest.approx(3.5)
+    assert book.orders_at(lb.OrderType.Buy, 10.00) == 2
+    assert book.quantity_at(lb.OrderType.Sell, 10.10) == pytest.approx(4.0)
+    assert book.orders_at(lb.OrderType.Sell, 10.10) == 1
+
+    # Empty levels and the wrong side report nothing
+    assert book.quantity_at(lb.OrderType.Sell, 10.00) == 0
+    assert book.orders_at(lb.OrderType.Buy, 9.95) == 0
+
+
+def test_spread_ticks(order_book: lb.OrderBook):
+    """Test the spread in ticks and that it agrees with the spread in price units."""
+    book = order_book
+    assert book.spread_ticks() is None
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    assert book.spread_ticks() is None
+    assert book.spread() is None
+
+    book.add(book.create_order(lb.OrderType.Sell, price=10.15, quantity=1.0))
+    assert book.spread_ticks() == 3
+    assert book.spread() == pytest.approx(3 * book.tick_size)
+
+
+def test_order_ids_at(order_book: lb.OrderBook):
+    """Test listing the order IDs at a level in the order matching consumes them."""
+    book = order_book
+    assert book.order_ids_at(lb.OrderType.Sell, 10.00) == []
+
+    orders = [
+        book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+        for _ in range(3)
+    ]
+    for order in orders:
+        book.add(order)
+    ids = book.order_ids_at(lb.OrderType.Sell, 10.00)
+    assert ids == [order.id for order in orders]
+
+    # Matching consumes the level in the listed order
+    fills = book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    assert [fill.sell_id for fill in fills] == ids[:2]
+    assert book.order_ids_at(lb.OrderType.Sell, 10.00) == ids[2:]
+
+
+def test_num_levels_and_orders(order_book: lb.OrderBook):
+    """Test counting price levels and resting orders per side."""
+    book = order_book
+    assert book.num_levels(lb.OrderType.Buy) == 0
+    assert book.num_orders(lb.OrderType.Buy) == 0
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.95, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=1.0))
+
+    assert book.num_levels(lb.OrderType.Buy) == 2
+    assert book.num_orders(lb.OrderType.Buy) == 3
+    assert book.num_levels(lb.OrderType.Sell) == 1
+    assert book.num_orders(lb.OrderType.Sell) == 1
+
+    # Filling the only ask empties its side
+    book.add(book.create_order(lb.OrderType.Buy, price=10.10, quantity=1.0))
+    assert book.num_levels(lb.OrderType.Sell) == 0
+    assert book.num_orders(lb.OrderType.Sell) == 0
+
+
+def test_orders_by_owner(order_book: lb.OrderBook):
+    """Test retrieving an owner's open orders."""
+    book = order_book
+    bid = book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0, owner="a")
+    ask = book.create_order(lb.OrderType.Sell, price=10.10, quantity=2.0, owner="a")
+    other = book.create_order(lb.OrderType.Sell, price=10.20, quantity=1.0, owner="b")
+    for order in (bid, ask, other):
+        book.add(order)
+    book.add(book.create_order(lb.OrderType.Buy, price=9.90, quantity=1.0))
+
+    assert [order.id for order in book.orders_by_owner("a")] == [bid.id, ask.id]
+    assert [order.id for order in book.orders_by_owner("b")] == [other.id]
+    assert book.orders_by_owner("c") == []
+
+    # Filled orders are no longer open
+    book.add(book.create_order(lb.OrderType.Buy, price=10.10, quantity=2.0, owner="c"))
+    assert [order.id for order in book.orders_by_owner("a")] == [bid.id]
+
+
+def test_notional(order_book: lb.OrderBook):
+    """Test the resting notional on each side of the book."""
+    book = order_book
+    assert book.buy_notional == 0
+    assert book.sell_notional == 0
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.00, quantity=3.0))
+
+    assert book.buy_notional == pytest.approx(29.50)
+    assert book.sell_notional == pytest.approx(33.00)
+
+
+def test_avg_price(order_book: lb.OrderBook):
+    """Test the size-weighted average resting price on each side of the book."""
+    book = order_book
+    assert book.avg_price(lb.OrderType.Buy) is None
+
+    book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=2.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.50, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=11.00, quantity=3.0))
+
+    assert book.avg_price(lb.OrderType.Buy) == pytest.approx(38.50 / 4.0)
+    assert book.avg_price(lb.OrderType.Sell) == pytest.approx(11.00)
+
+
+def test_duplicate_order_rejected(order_book: lb.OrderBook):
+    """Test that adding the same order twice is rejected without corrupting the book."""
+    book = order_book
+    order = book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.0)
+    book.add(order)
+
+    with pytest.raises(ValueError, match="already"):
+        book.add(order)
+    assert book.orders_at(lb.OrderType.Buy, 10.00) == 1
+
+    # Filled orders keep their ID reserved too
+    sell = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    book.add(sell)
+    with pytest.raises(ValueError):
+        book.add(sell)
+
+    # Canceled orders are forgotten, so their ID may be added again
+    again = book.create_order(lb.OrderType.Buy, price=9.50, quantity=2.0)
+    book.add(again)
+    book.cancel(again.id)
+    assert again.id not in book
+    assert book.add(again) == []
+    assert book.get_order(again.id).status == lb.OrderStatus.Open
+    assert book.orders_at(lb.OrderType.Buy, 9.50) == 1
+    assert book.validate()
+
+
+@pytest.mark.parametrize("matching_mode", [lb.MatchingMode.Fifo, lb.MatchingMode.ProRata])
+def test_validate_after_mixed_operations(matching_mode):
+    """Test that the book stays internally consistent through partial fills and edits."""
+    book = lb.OrderBook(tick_size=0.05, matching_mode=matching_mode)
+    assert book.validate()
+
+    resting = [
+        book.create_order(lb.OrderType.Sell, price=10.00, quantity=3.0),
+        book.create_iceberg_order(lb.OrderType.Sell, 10.00, 5.0, 1.0),
+        book.create_order(lb.OrderType.Sell, price=10.05, quantity=2.0),
+        book.create_order(lb.OrderType.Buy, price=9.90, quantity=4.0),
+    ]
+    for order in resting:
+        book.add(order)
+        assert book.validate()
+
+    steps = [
+        lambda: book.add(book.create_order(lb.OrderType.Buy, price=10.00, quantity=1.5)),
+        lambda: book.add(book.create_order(lb.OrderType.Buy, price=10.05, quantity=4.0)),
+        lambda: book.add(book.create_stop_order(lb.OrderType.Sell, 9.50, 1.0)),
+        lambda: book.modify_quantity(resting[3].id, 2.0),
+        lambda: book.replace(resting[3].id, 9.95, 3.0),
+        lambda: book.add(book.create_order(lb.OrderType.Sell, price=9.95, quantity=1.0)),
+        lambda: book.cancel(resting[2].id),
+        lambda: book.add(book.create_market_order(lb.OrderType.Buy, 2.5)),
+        lambda: book.cancel_all(),
+    ]
+    for step in steps:
+        step()
+        assert book.validate()
+
+    # An open order that is tracked but never queued breaks the invariants
+    book.update_order(book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0))
+    assert not book.validate()
+
+
+def test_snapshot_with_invalid_tick_size_rejected():
+    """Test that restoring a snapshot with a zero tick size fails cleanly."""
+    data = lb.OrderBook(tick_size=0.05).to_json().replace('"tick_size":0.05', '"tick_size":0.0')
+    with pytest.raises(ValueError):
+        lb.OrderBook.from_json(data)
+
+
+def test_add_detailed(order_book: lb.OrderBook):
+    """Test that add_detailed reports the fills and the resting remainder."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=2.0))
+
+    fills, remainder = book.add_detailed(
+        book.create_order(lb.OrderType.Buy, price=10.00, quantity=5.0)
+    )
+    assert [fill.quantity for fill in fills] == [2.0]
+    assert remainder.quantity == 3.0
+    assert remainder.status == lb.OrderStatus.Open
+    assert remainder.id in book
+
+    # Fully filled and canceled orders leave no remainder
+    fills, remainder = book.add_detailed(
+        book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    )
+    assert len(fills) == 1 and remainder is None
+    ioc = book.create_order(
+        lb.OrderType.Sell, price=10.00, quantity=5.0, time_in_force=lb.TimeInForce.IOC
+    )
+    fills, remainder = book.add_detailed(ioc)
+    assert [fill.quantity for fill in fills] == [2.0]
+    assert remainder is None
+
+
+def test_add_summary(order_book: lb.OrderBook):
+    """Test that add_summary aggregates the fills that add would return."""
+    book = order_book
+    book.add(book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0))
+    book.add(book.create_order(lb.OrderType.Sell, price=10.10, quantity=3.0))
+
+    filled, average_price, count = book.add_summary(
+        book.create_order(lb.OrderType.Buy, price=10.10, quantity=2.0)
+    )
+    assert filled == 2.0
+    assert average_price == pytest.approx(10.05)
+    assert count == 2
+    assert book.best_ask == (10.10, 2.0)
+
+    # An order that does not match reports nothing and rests
+    summary = book.add_summary(
+        book.create_order(lb.OrderType.Buy, price=9.00, quantity=1.0)
+    )
+    assert summary == (0.0, 0.0, 0)
+    assert book.best_bid == (9.00, 1.0)
+
+
+def test_add_by_level(order_book: lb.OrderBook):
+    """Test that add_by_level groups a sweeping order's fills by price level."""
+    book = order_book
+    first = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    second = book.create_order(lb.OrderType.Sell, price=10.00, quantity=1.0)
+    deeper = book.create_order(lb.OrderType.Sell, price=10.10, quantity=3.0)
+    for order in (first, second, deeper):
+        book.add(order)
+
+    levels = book.add_by_level(
+        book.create_order(lb.OrderType.Buy, price=10.10, quantity=4.0)
+    )
+    assert [price for price, _ in levels] == [10.00, 10.10]
+    assert [fill.sell_id for fill in levels[0][1]] == [first.id, second.id]
+    assert [(fill.sell_id, fill.quantity) for fill in levels[1][1]] == [(deeper.id, 2.0)]
+
+    assert book.add_by_level(book.create_order(lb.OrderType.Buy, 9.00, 1.0)) == []
@@ -128,3 +128,68 @@ def test_no_fill_for_non_matching_orders(order_book):
     assert len(fills) == 0
     assert buy_order.status == lb.OrderStatus.Open
     assert sell_order.status == lb.OrderStatus.Open
+
+
+def test_fill_aggressor_side(order_book):
+    """Test that fills record the taker's side and the correct buy/sell IDs."""
+    sell_order = order_book.create_order(lb.OrderType.Sell, price=100.0, quantity=5.0)
+    buy_order = order_book.create_order(lb.OrderType.Buy, price=100.0, quantity=5.0)
+
+    order_book.add(sell_order)
+    fills = order_book.add(buy_order)
+
+    assert len(fills) == 1
+    fill = fills[0]
+    assert fill.aggressor_side == lb.OrderType.Buy
+    assert fill.buy_id == buy_order.id
+    assert fill.sell_id == sell_order.id
+    assert "Aggressor: Buy" in repr(fill)
+
+    order_book.add(
+        order_book.create_order(lb.OrderType.Buy, price=100.0, quantity=5.0)
+    )
+    fills = order_book.add(
+        order_book.create_order(lb.OrderType.Sell, price=100.0, quantity=5.0)
+    )
+    assert fills[0].aggressor_side == lb.OrderType.Sell
+
+    # The original five-argument constructor still works, with no aggressor
+    manual = lb.Fill(1.0, 100.0, buy_order.id, sell_order.id, 42)
+    assert manual.aggressor_side is None
+    assert "Aggressor: Unknown" in repr(manual)
+
+
+def test_order_with_id(order_book):
+    """Test constructing orders with an explicit ID."""
+    order = lb.Order.with_id("ext-1", lb.OrderType.Buy, 100, 10.0, owner="a")
+    assert order.id == "ext-1"
+    assert order.price_in_ticks == 100
+    assert order.quantity == 10.0
+    assert order.owner == "a"
+
+    # The ID is kept when the order is added to a book
+    order_book.add(order)
+    assert order_book.get_order("ext-1").quantity == 10.0
+
+    # The default constructor still generates a fresh ID
+    first = lb.Order(lb.OrderType.Buy, 100, 10.0)
+    second = lb.Order(lb.OrderType.Buy, 100, 10.0)
+    assert first.id != second.id
+
+    with pytest.raises(lb.OrderRejectedError) as excinfo:
+        lb.Order.with_id("", lb.OrderType.Buy, 100, 10.0)
+    assert excinfo.value.reason == lb.OrderRejectReason.EmptyOrderId
+
+
+def test_order_equality_by_id():
+    """Test that orders compare equal and hash by ID, not by their fields."""
+    order = lb.Order.with_id("order-1", lb.OrderType.Buy, 100, 10.0)
+    same_id = lb.Order.with_id("order-1", lb.OrderType.Sell, 105, 2.0)
+    other = lb.Order.with_id("order-2", lb.OrderType.Buy, 100, 10.0)
+
+    assert order == same_id
+    assert order != other
+    assert order != "order-1"
+    assert hash(order) == hash(same_id)
+    assert len({order, same_id, other}) == 2
+    assert {order: "first"}[same_id] == "first"